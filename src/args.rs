@@ -0,0 +1,278 @@
+//! Parsing for `key = value` style macro arguments.
+use crate::errors::{self, ErrorKind};
+use proc_macro2::{Span, TokenStream};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, Ident, Index, Lit, LitInt, LitStr, Member, Token, Type};
+
+/// The right-hand side of a single macro argument.
+pub(crate) enum ArgValue {
+    /// A bare name, e.g. `#[foo(gen_tests)]`.
+    Flag,
+    /// A literal, e.g. `#[foo(reduce = "newargs")]`.
+    Lit(Lit),
+    /// A type or path, e.g. `#[foo(item = Instruction)]`.
+    Type(Box<Type>),
+    /// A parenthesized list, e.g. `#[foo(exclude(a, b))]`.
+    List(TokenStream),
+}
+
+pub(crate) struct Arg {
+    pub(crate) name: Ident,
+    pub(crate) value: ArgValue,
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.call(Ident::parse_any)?;
+        let value = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            if input.peek(Lit) {
+                ArgValue::Lit(input.parse()?)
+            } else {
+                ArgValue::Type(Box::new(input.parse()?))
+            }
+        } else if input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            ArgValue::List(content.parse()?)
+        } else {
+            ArgValue::Flag
+        };
+        Ok(Self { name, value })
+    }
+}
+
+/// Comma-separated macro arguments.
+///
+/// Callers take out the arguments they understand and then call [`MacroArgs::finish`],
+/// which rejects anything left over.
+pub(crate) struct MacroArgs {
+    args: Vec<Arg>,
+}
+
+impl Parse for MacroArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let args = Punctuated::<Arg, Token![,]>::parse_terminated(input)?;
+        Ok(Self {
+            args: args.into_iter().collect(),
+        })
+    }
+}
+
+fn missing(name: &str) -> syn::Error {
+    errors::at(
+        ErrorKind::MissingArgument,
+        Span::call_site(),
+        format!("missing required argument `{}`", name),
+    )
+}
+
+fn ident_error(name: &Ident) -> syn::Error {
+    errors::at(
+        ErrorKind::InvalidArgument,
+        name.span(),
+        format!("expected `{} = some_name`", name),
+    )
+}
+
+fn member_error(name: &Ident) -> syn::Error {
+    errors::at(
+        ErrorKind::InvalidArgument,
+        name.span(),
+        format!("expected `{} = field_name` or `{} = 0`", name, name),
+    )
+}
+
+impl MacroArgs {
+    pub(crate) fn take(&mut self, name: &str) -> Option<Arg> {
+        let idx = self.args.iter().position(|a| a.name == name)?;
+        Some(self.args.remove(idx))
+    }
+
+    /// Whether a bare flag was passed.
+    pub(crate) fn flag(&mut self, name: &str) -> syn::Result<bool> {
+        match self.take(name) {
+            None => Ok(false),
+            Some(Arg {
+                value: ArgValue::Flag,
+                ..
+            }) => Ok(true),
+            Some(arg) => Err(errors::at(
+                ErrorKind::InvalidArgument,
+                arg.name.span(),
+                format!("`{}` does not take a value", name),
+            )),
+        }
+    }
+
+    pub(crate) fn ty(&mut self, name: &str) -> syn::Result<Option<Type>> {
+        match self.take(name) {
+            None => Ok(None),
+            Some(Arg {
+                value: ArgValue::Type(ty),
+                ..
+            }) => Ok(Some(*ty)),
+            Some(arg) => Err(errors::at(
+                ErrorKind::InvalidArgument,
+                arg.name.span(),
+                format!("expected `{} = SomeType`", name),
+            )),
+        }
+    }
+
+    pub(crate) fn required_ty(&mut self, name: &str) -> syn::Result<Type> {
+        self.ty(name)?.ok_or_else(|| missing(name))
+    }
+
+    pub(crate) fn lit_str(&mut self, name: &str) -> syn::Result<Option<LitStr>> {
+        match self.take(name) {
+            None => Ok(None),
+            Some(Arg {
+                value: ArgValue::Lit(Lit::Str(s)),
+                ..
+            }) => Ok(Some(s)),
+            Some(arg) => Err(errors::at(
+                ErrorKind::InvalidArgument,
+                arg.name.span(),
+                format!("expected `{} = \"...\"`", name),
+            )),
+        }
+    }
+
+    pub(crate) fn lit_int(&mut self, name: &str) -> syn::Result<Option<LitInt>> {
+        match self.take(name) {
+            None => Ok(None),
+            Some(Arg {
+                value: ArgValue::Lit(Lit::Int(i)),
+                ..
+            }) => Ok(Some(i)),
+            Some(arg) => Err(errors::at(
+                ErrorKind::InvalidArgument,
+                arg.name.span(),
+                format!("expected `{} = <integer>`", name),
+            )),
+        }
+    }
+
+    pub(crate) fn required_lit_int(&mut self, name: &str) -> syn::Result<LitInt> {
+        self.lit_int(name)?.ok_or_else(|| missing(name))
+    }
+
+    pub(crate) fn required_lit_str(&mut self, name: &str) -> syn::Result<LitStr> {
+        self.lit_str(name)?.ok_or_else(|| missing(name))
+    }
+
+    /// A bare identifier, e.g. a method name.
+    pub(crate) fn ident(&mut self, name: &str) -> syn::Result<Option<Ident>> {
+        match self.take(name) {
+            None => Ok(None),
+            Some(Arg {
+                value: ArgValue::Type(ty),
+                name,
+            }) => match *ty {
+                Type::Path(p) if p.qself.is_none() && p.path.get_ident().is_some() => {
+                    Ok(Some(p.path.get_ident().unwrap().clone()))
+                }
+                _ => Err(ident_error(&name)),
+            },
+            Some(arg) => Err(ident_error(&arg.name)),
+        }
+    }
+
+    /// A struct field, given either as a name (`field = inner`) or an index (`field = 0`).
+    pub(crate) fn member(&mut self, name: &str) -> syn::Result<Option<Member>> {
+        match self.take(name) {
+            None => Ok(None),
+            Some(Arg {
+                value: ArgValue::Lit(Lit::Int(i)),
+                ..
+            }) => Ok(Some(Member::Unnamed(Index {
+                index: i.base10_parse()?,
+                span: i.span(),
+            }))),
+            Some(Arg {
+                value: ArgValue::Type(ty),
+                name,
+            }) => match *ty {
+                Type::Path(p) if p.qself.is_none() && p.path.get_ident().is_some() => {
+                    Ok(Some(Member::Named(p.path.get_ident().unwrap().clone())))
+                }
+                _ => Err(member_error(&name)),
+            },
+            Some(arg) => Err(member_error(&arg.name)),
+        }
+    }
+
+    pub(crate) fn required_member(&mut self, name: &str) -> syn::Result<Member> {
+        self.member(name)?.ok_or_else(|| missing(name))
+    }
+
+    /// A parenthesized list of identifiers, e.g. `exclude(a, b)`.
+    pub(crate) fn ident_list(&mut self, name: &str) -> syn::Result<Option<Vec<Ident>>> {
+        match self.take(name) {
+            None => Ok(None),
+            Some(Arg {
+                value: ArgValue::List(tokens),
+                ..
+            }) => {
+                let parser = Punctuated::<Ident, Token![,]>::parse_terminated;
+                Ok(Some(
+                    syn::parse::Parser::parse2(parser, tokens)?
+                        .into_iter()
+                        .collect(),
+                ))
+            }
+            Some(arg) => Err(errors::at(
+                ErrorKind::InvalidArgument,
+                arg.name.span(),
+                format!("expected `{}(a, b, ...)`", name),
+            )),
+        }
+    }
+
+    /// A parenthesized list of struct fields, given as names or indices, e.g. `heap(0, data)`.
+    pub(crate) fn member_list(&mut self, name: &str) -> syn::Result<Option<Vec<Member>>> {
+        match self.take(name) {
+            None => Ok(None),
+            Some(Arg {
+                value: ArgValue::List(tokens),
+                ..
+            }) => {
+                let parser = Punctuated::<Member, Token![,]>::parse_terminated;
+                Ok(Some(
+                    syn::parse::Parser::parse2(parser, tokens)?
+                        .into_iter()
+                        .collect(),
+                ))
+            }
+            Some(arg) => Err(errors::at(
+                ErrorKind::InvalidArgument,
+                arg.name.span(),
+                format!("expected `{}(field, 0, ...)`", name),
+            )),
+        }
+    }
+
+    /// Take the first bare flag, whatever its name.
+    pub(crate) fn any_flag(&mut self) -> Option<Ident> {
+        let idx = self
+            .args
+            .iter()
+            .position(|a| matches!(a.value, ArgValue::Flag))?;
+        Some(self.args.remove(idx).name)
+    }
+
+    /// Error on any argument that wasn't taken.
+    pub(crate) fn finish(self) -> syn::Result<()> {
+        match self.args.into_iter().next() {
+            None => Ok(()),
+            Some(arg) => Err(errors::at(
+                ErrorKind::UnrecognizedArgument,
+                arg.name.span(),
+                format!("unrecognized argument `{}`", arg.name),
+            )),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Generated code that differs between pyo3's GIL-ref API and the `Bound` API of pyo3 0.21+.
+//!
+//! The `bound` feature switches to the `Bound` API.
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ImplItem;
+
+/// A `bytes` object tied to the GIL lifetime `'a`.
+pub(crate) fn py_bytes() -> TokenStream {
+    owned(quote! { PyBytes })
+}
+
+/// An object of the Python type `ty`, e.g. `PyDict`, tied to the GIL lifetime `'a`.
+pub(crate) fn owned(ty: TokenStream) -> TokenStream {
+    if cfg!(feature = "bound") {
+        quote! { pyo3::Bound<'a, pyo3::types::#ty> }
+    } else {
+        quote! { &'a pyo3::types::#ty }
+    }
+}
+
+/// The constructor of the Python type `ty`, e.g. `PyDict::new`, which takes `py` first.
+pub(crate) fn new(ty: TokenStream) -> TokenStream {
+    if cfg!(feature = "bound") {
+        quote! { pyo3::types::#ty::new_bound }
+    } else {
+        quote! { pyo3::types::#ty::new }
+    }
+}
+
+/// A borrowed argument of the Python type `ty`, e.g. `PyDict`.
+pub(crate) fn arg(ty: TokenStream) -> TokenStream {
+    if cfg!(feature = "bound") {
+        quote! { &pyo3::Bound<'_, pyo3::types::#ty> }
+    } else {
+        quote! { &pyo3::types::#ty }
+    }
+}
+
+/// Bring the `Bound` method traits like `PyAnyMethods` into scope in a generated method body.
+pub(crate) fn methods_in_scope() -> TokenStream {
+    if cfg!(feature = "bound") {
+        quote! {
+            #[allow(unused_imports)]
+            use pyo3::types::{
+                PyAnyMethods, PyByteArrayMethods, PyBytesMethods, PyDictMethods, PyModuleMethods,
+                PyStringMethods, PyTupleMethods, PyTypeMethods,
+            };
+        }
+    } else {
+        TokenStream::new()
+    }
+}
+
+/// The Python type object of the Rust type `ty`, e.g. `py.get_type::<Self>()`.
+pub(crate) fn get_type(py: TokenStream, ty: TokenStream) -> TokenStream {
+    if cfg!(feature = "bound") {
+        quote! { #py.get_type_bound::<#ty>() }
+    } else {
+        quote! { #py.get_type::<#ty>() }
+    }
+}
+
+/// Import the Python module `name`.
+pub(crate) fn import(py: TokenStream, name: &str) -> TokenStream {
+    if cfg!(feature = "bound") {
+        quote! { #py.import_bound(#name) }
+    } else {
+        quote! { #py.import(#name) }
+    }
+}
+
+/// Get a `PyBuffer<u8>` for the object `ob`.
+pub(crate) fn buffer(ob: TokenStream) -> TokenStream {
+    if cfg!(feature = "bound") {
+        quote! { pyo3::buffer::PyBuffer::<u8>::get_bound(#ob) }
+    } else {
+        quote! { pyo3::buffer::PyBuffer::<u8>::get(#ob) }
+    }
+}
+
+/// The signature of the required method of `impl<'source> FromPyObject<'source>`, taking `ob`.
+pub(crate) fn extract_signature() -> TokenStream {
+    if cfg!(feature = "bound") {
+        quote! { fn extract_bound(ob: &pyo3::Bound<'source, pyo3::types::PyAny>) -> pyo3::prelude::PyResult<Self> }
+    } else {
+        quote! { fn extract(ob: &'source pyo3::types::PyAny) -> pyo3::prelude::PyResult<Self> }
+    }
+}
+
+/// The function creating a `bytes` object from `(py, &[u8])`.
+pub(crate) fn new_py_bytes() -> TokenStream {
+    new(quote! { PyBytes })
+}
+
+/// The `(callable, args)` pair returned by `__reduce__`, tied to the GIL lifetime `'a` with `bound`.
+pub(crate) fn reduce_output() -> TokenStream {
+    if cfg!(feature = "bound") {
+        quote! { (pyo3::Bound<'a, pyo3::PyAny>, pyo3::Bound<'a, pyo3::PyAny>) }
+    } else {
+        quote! { (pyo3::prelude::PyObject, pyo3::prelude::PyObject) }
+    }
+}
+
+/// `__bytes__` using `CommonMethods::pybytes`.
+pub(crate) fn bytes_method() -> ImplItem {
+    let py_bytes = py_bytes();
+    ImplItem::Verbatim(
+        quote! {pub fn __bytes__<'a>(&self, py: pyo3::prelude::Python<'a>) -> #py_bytes  {self.pybytes(py)}},
+    )
+}
+
+/// `__reduce__` using `CommonMethods::pyreduce`, which takes `py` with `bound`.
+pub(crate) fn reduce_method() -> ImplItem {
+    let output = reduce_output();
+    if cfg!(feature = "bound") {
+        ImplItem::Verbatim(
+            quote! { pub fn __reduce__<'a>(&self, py: pyo3::prelude::Python<'a>) -> pyo3::prelude::PyResult<#output> {self.pyreduce(py)} },
+        )
+    } else {
+        ImplItem::Verbatim(
+            quote! { pub fn __reduce__(&self) -> pyo3::prelude::PyResult<#output> {self.pyreduce()} },
+        )
+    }
+}
+
+/// The `&[u8]` contents of a `bytes` object returned by `CommonMethods::pybytes`.
+pub(crate) fn as_bytes(bytes: TokenStream) -> TokenStream {
+    if cfg!(feature = "bound") {
+        quote! { pyo3::types::PyBytesMethods::as_bytes(&#bytes) }
+    } else {
+        quote! { #bytes.as_bytes() }
+    }
+}
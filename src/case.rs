@@ -0,0 +1,69 @@
+//! Case conversion for variant names, following serde's `rename_all` rules.
+use crate::errors::{self, ErrorKind};
+use syn::LitStr;
+
+#[derive(Clone, Copy)]
+pub(crate) enum RenameRule {
+    None,
+    LowerCase,
+    UpperCase,
+    PascalCase,
+    CamelCase,
+    SnakeCase,
+    ScreamingSnakeCase,
+    KebabCase,
+    ScreamingKebabCase,
+}
+
+impl RenameRule {
+    pub(crate) fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        Ok(match lit.value().as_str() {
+            "lowercase" => Self::LowerCase,
+            "UPPERCASE" => Self::UpperCase,
+            "PascalCase" => Self::PascalCase,
+            "camelCase" => Self::CamelCase,
+            "snake_case" => Self::SnakeCase,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnakeCase,
+            "kebab-case" => Self::KebabCase,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebabCase,
+            _ => {
+                return Err(errors::spanned(
+                    ErrorKind::InvalidArgument,
+                    lit,
+                    "unknown rename rule, expected one of \"lowercase\", \"UPPERCASE\", \
+                     \"PascalCase\", \"camelCase\", \"snake_case\", \"SCREAMING_SNAKE_CASE\", \
+                     \"kebab-case\", \"SCREAMING-KEBAB-CASE\"",
+                ))
+            }
+        })
+    }
+
+    /// Rename a `PascalCase` variant name.
+    pub(crate) fn apply(self, variant: &str) -> String {
+        match self {
+            Self::None | Self::PascalCase => variant.to_owned(),
+            Self::LowerCase => variant.to_ascii_lowercase(),
+            Self::UpperCase => variant.to_ascii_uppercase(),
+            Self::CamelCase => {
+                let mut chars = variant.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => String::new(),
+                }
+            }
+            Self::SnakeCase => {
+                let mut snake = String::new();
+                for (i, ch) in variant.char_indices() {
+                    if i > 0 && ch.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(ch.to_ascii_lowercase());
+                }
+                snake
+            }
+            Self::ScreamingSnakeCase => Self::SnakeCase.apply(variant).to_ascii_uppercase(),
+            Self::KebabCase => Self::SnakeCase.apply(variant).replace('_', "-"),
+            Self::ScreamingKebabCase => Self::ScreamingSnakeCase.apply(variant).replace('_', "-"),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! The `cfg = ...` argument every attribute macro accepts, which puts the items the macro
+//! injects behind a `#[cfg(...)]`, e.g. `#[json_file_methods(cfg = not(target_arch = "wasm32"))]`.
+//!
+//! The macro's output is compared with its input. Methods added to the impl block get the
+//! `#[cfg]`, attributes added to the item become `#[cfg_attr]`, and any other items the macro
+//! emits (extra impl blocks, helper classes, tests) get the `#[cfg]`. The item itself is
+//! always kept, and so are compile errors.
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{Attribute, ImplItem, Item};
+
+/// The name, or self type for impls, identifying the item a macro was applied to in its output.
+fn item_key(item: &Item) -> Option<String> {
+    let key = match item {
+        Item::Impl(i) => format!(
+            "impl {} {}",
+            i.trait_
+                .as_ref()
+                .map(|t| t.1.to_token_stream())
+                .unwrap_or_default(),
+            i.self_ty.to_token_stream()
+        ),
+        Item::Struct(i) => i.ident.to_string(),
+        Item::Enum(i) => i.ident.to_string(),
+        Item::Fn(i) => i.sig.ident.to_string(),
+        Item::Mod(i) => i.ident.to_string(),
+        _ => return None,
+    };
+    Some(key)
+}
+
+fn attrs_mut(item: &mut Item) -> Option<&mut Vec<Attribute>> {
+    match item {
+        Item::Impl(i) => Some(&mut i.attrs),
+        Item::Struct(i) => Some(&mut i.attrs),
+        Item::Enum(i) => Some(&mut i.attrs),
+        Item::Fn(i) => Some(&mut i.attrs),
+        Item::Mod(i) => Some(&mut i.attrs),
+        _ => None,
+    }
+}
+
+fn impl_item_attrs_mut(item: &mut ImplItem) -> Option<&mut Vec<Attribute>> {
+    match item {
+        ImplItem::Method(i) => Some(&mut i.attrs),
+        ImplItem::Const(i) => Some(&mut i.attrs),
+        ImplItem::Type(i) => Some(&mut i.attrs),
+        ImplItem::Macro(i) => Some(&mut i.attrs),
+        _ => None,
+    }
+}
+
+fn to_string(tokens: impl ToTokens) -> String {
+    tokens.to_token_stream().to_string()
+}
+
+/// Put what the macro added to the item it was applied to behind `predicate`.
+fn gate_main_item(item: &mut Item, original: &mut Item, predicate: &TokenStream) {
+    let original_attrs: Vec<String> = attrs_mut(original)
+        .map(|attrs| attrs.iter().map(to_string).collect())
+        .unwrap_or_default();
+    if let Some(attrs) = attrs_mut(item) {
+        for attr in attrs.iter_mut() {
+            if !original_attrs.contains(&to_string(&*attr)) {
+                let (path, tokens) = (&attr.path, &attr.tokens);
+                *attr = syn::parse_quote!(#[cfg_attr(#predicate, #path #tokens)]);
+            }
+        }
+    }
+    if let (Item::Impl(item), Item::Impl(original)) = (item, original) {
+        let original_items: Vec<String> = original.items.iter().map(to_string).collect();
+        for impl_item in &mut item.items {
+            if original_items.contains(&to_string(&*impl_item)) {
+                continue;
+            }
+            if let Some(attrs) = impl_item_attrs_mut(impl_item) {
+                attrs.insert(0, syn::parse_quote!(#[cfg(#predicate)]));
+            }
+        }
+    }
+}
+
+fn is_compile_error(item: &Item) -> bool {
+    matches!(item, Item::Macro(m) if m.mac.path.is_ident("compile_error"))
+}
+
+/// Gate the items in `output` that weren't in `input` behind `#[cfg(predicate)]`.
+///
+/// Output that doesn't parse as items is returned unchanged, so the compiler reports on it.
+pub(crate) fn apply(
+    output: TokenStream,
+    input: TokenStream,
+    predicate: &TokenStream,
+) -> TokenStream {
+    let (mut original, file) = match (
+        syn::parse2::<Item>(input),
+        syn::parse2::<syn::File>(output.clone()),
+    ) {
+        (Ok(original), Ok(file)) => (original, file),
+        _ => return output,
+    };
+    let key = item_key(&original);
+    let mut main_found = false;
+    let mut gated = TokenStream::new();
+    for mut item in file.items {
+        if !main_found && key.is_some() && item_key(&item) == key {
+            main_found = true;
+            gate_main_item(&mut item, &mut original, predicate);
+            item.to_tokens(&mut gated);
+        } else if is_compile_error(&item) {
+            item.to_tokens(&mut gated);
+        } else {
+            gated.extend(quote! { #[cfg(#predicate)] #item });
+        }
+    }
+    gated
+}
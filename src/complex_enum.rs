@@ -0,0 +1,144 @@
+//! Expansion for `#[complex_enum]`.
+use crate::errors::{self, ErrorKind};
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Attribute, Fields, ItemEnum, LitStr};
+
+fn doc_attrs(attrs: &[Attribute]) -> Vec<&Attribute> {
+    attrs.iter().filter(|a| a.path.is_ident("doc")).collect()
+}
+
+/// Names for a variant's fields: the field names for struct variants,
+/// `field_0`, `field_1`, ... for tuple variants.
+fn field_names(fields: &Fields) -> Vec<Ident> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| match &f.ident {
+            Some(ident) => ident.clone(),
+            None => format_ident!("field_{}", i),
+        })
+        .collect()
+}
+
+/// Build the pattern (or constructor) for an enum variant binding each field to its name.
+fn variant_pattern(path: TokenStream, fields: &Fields, names: &[Ident]) -> TokenStream {
+    match fields {
+        Fields::Named(_) => quote! { #path { #(#names),* } },
+        Fields::Unnamed(_) => quote! { #path ( #(#names),* ) },
+        Fields::Unit => path,
+    }
+}
+
+pub(crate) fn expand(module: Option<LitStr>, ast: ItemEnum) -> syn::Result<TokenStream> {
+    if !ast.generics.params.is_empty() {
+        return Err(errors::spanned(
+            ErrorKind::Generic,
+            &ast.generics,
+            "`complex_enum` does not support generic enums",
+        ));
+    }
+    let enum_name = &ast.ident;
+    let vis = &ast.vis;
+    let union_name = format_ident!("{}Union", enum_name);
+    let pyclass_attr = match &module {
+        Some(module) => quote! { #[pyo3::pyclass(module = #module)] },
+        None => quote! { #[pyo3::pyclass] },
+    };
+    let mut classes = Vec::new();
+    let mut union_variants = Vec::new();
+    let mut to_union_arms = Vec::new();
+    let mut from_union_arms = Vec::new();
+    for variant in &ast.variants {
+        let variant_name = &variant.ident;
+        let class_name = format_ident!("{}{}", enum_name, variant_name);
+        let names = field_names(&variant.fields);
+        let types: Vec<_> = variant.fields.iter().map(|f| &f.ty).collect();
+        let field_docs: Vec<_> = variant.fields.iter().map(|f| doc_attrs(&f.attrs)).collect();
+        let variant_docs = doc_attrs(&variant.attrs);
+        classes.push(quote! {
+            #(#variant_docs)*
+            #pyclass_attr
+            #[derive(Clone)]
+            #vis struct #class_name {
+                #(
+                    #(#field_docs)*
+                    #[pyo3(get)]
+                    pub #names: #types,
+                )*
+            }
+
+            #[pyo3::pymethods]
+            impl #class_name {
+                #[new]
+                pub fn new(#(#names: #types),*) -> Self {
+                    Self { #(#names),* }
+                }
+            }
+        });
+        union_variants.push(quote! { #variant_name(#class_name) });
+        let enum_pattern = variant_pattern(
+            quote! { #enum_name::#variant_name },
+            &variant.fields,
+            &names,
+        );
+        to_union_arms.push(quote! {
+            #enum_pattern => Self::#variant_name(#class_name { #(#names),* })
+        });
+        from_union_arms.push(quote! {
+            #union_name::#variant_name(#class_name { #(#names),* }) => #enum_pattern
+        });
+    }
+    let union_def = quote! {
+        /// One Python class per variant of the original enum.
+        #[derive(Clone)]
+        #vis enum #union_name {
+            #(#union_variants),*
+        }
+    };
+    let union_ast: syn::DeriveInput = syn::parse2(union_def.clone())?;
+    let union_into_py = crate::union_into_py(&union_ast)?;
+    let union_from_py = crate::union_from_py(&union_ast)?;
+    let extract = crate::bound::extract_signature();
+    let methods_in_scope = crate::bound::methods_in_scope();
+    Ok(quote! {
+        #ast
+
+        #(#classes)*
+
+        #union_def
+
+        #union_into_py
+
+        #union_from_py
+
+        impl From<#enum_name> for #union_name {
+            fn from(original: #enum_name) -> Self {
+                match original {
+                    #(#to_union_arms),*
+                }
+            }
+        }
+
+        impl From<#union_name> for #enum_name {
+            fn from(union: #union_name) -> Self {
+                match union {
+                    #(#from_union_arms),*
+                }
+            }
+        }
+
+        impl pyo3::IntoPy<pyo3::prelude::PyObject> for #enum_name {
+            fn into_py(self, py: pyo3::prelude::Python<'_>) -> pyo3::prelude::PyObject {
+                pyo3::IntoPy::into_py(#union_name::from(self), py)
+            }
+        }
+
+        impl<'source> pyo3::FromPyObject<'source> for #enum_name {
+            #extract {
+                #methods_in_scope
+                ob.extract::<#union_name>().map(Self::from)
+            }
+        }
+    })
+}
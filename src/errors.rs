@@ -0,0 +1,130 @@
+//! Errors reported by the macros.
+//!
+//! Every message starts with a stable identifier for its kind of failure, e.g.
+//! `solders-macros(unrecognized-argument): unrecognized argument `foo``, so tools and tests
+//! can match on the kind without depending on the wording that follows.
+//!
+//! Each kind has a compile-fail example below, except `io`, and a snapshot of its full
+//! message in `tests/ui`.
+//!
+//! `wrong-item`: the macro is applied to the wrong kind or shape of item.
+//!
+//! ```compile_fail
+//! #[solders_macros::pyhash]
+//! struct Foo;
+//! ```
+//!
+//! `missing-argument`: a required argument isn't given.
+//!
+//! ```compile_fail
+//! struct Foo(Vec<u8>);
+//!
+//! #[solders_macros::pyiterator(item = u8)]
+//! impl Foo {}
+//! ```
+//!
+//! `unrecognized-argument`: an argument the macro doesn't take.
+//!
+//! ```compile_fail
+//! #[derive(Debug)]
+//! struct Foo;
+//!
+//! #[solders_macros::debug_repr(colour = "red")]
+//! impl Foo {}
+//! ```
+//!
+//! `invalid-argument`: an argument with the wrong form or value.
+//!
+//! ```compile_fail
+//! #[derive(Clone, Copy)]
+//! #[solders_macros::enum_str_mapping(rename_all = "Sentence case")]
+//! enum Foo {
+//!     A,
+//! }
+//! ```
+//!
+//! `generic`: generics where the generated code can't support them.
+//!
+//! ```compile_fail
+//! struct Foo<T>(T);
+//!
+//! #[solders_macros::register_class(submodule = "foo")]
+//! impl<T> Foo<T> {}
+//! ```
+//!
+//! `duplicate-method`: the impl already defines a method the macro generates.
+//!
+//! ```compile_fail
+//! #[derive(Debug)]
+//! struct Foo;
+//!
+//! #[solders_macros::debug_repr]
+//! impl Foo {
+//!     fn __repr__(&self) -> String {
+//!         String::new()
+//!     }
+//! }
+//! ```
+//!
+//! `conflict`: macros on the impl generate methods that don't work together.
+//!
+//! ```compile_fail
+//! struct Foo(u8);
+//!
+//! #[solders_macros::common_methods]
+//! #[solders_macros::getnewargs]
+//! impl Foo {
+//!     #[new]
+//!     fn new(value: u8) -> Self {
+//!         Self(value)
+//!     }
+//! }
+//! ```
+//!
+//! `io`: writing a stub or metadata file failed. `tests/ui_io.rs` triggers it by pointing
+//! `OUT_DIR` at a file.
+use proc_macro2::Span;
+use quote::ToTokens;
+use std::fmt::Display;
+
+#[derive(Clone, Copy)]
+pub(crate) enum ErrorKind {
+    WrongItem,
+    MissingArgument,
+    UnrecognizedArgument,
+    InvalidArgument,
+    Generic,
+    DuplicateMethod,
+    Conflict,
+    Io,
+}
+
+impl ErrorKind {
+    /// The identifier that starts every message of this kind. Don't change these.
+    pub(crate) fn id(self) -> &'static str {
+        match self {
+            Self::WrongItem => "wrong-item",
+            Self::MissingArgument => "missing-argument",
+            Self::UnrecognizedArgument => "unrecognized-argument",
+            Self::InvalidArgument => "invalid-argument",
+            Self::Generic => "generic",
+            Self::DuplicateMethod => "duplicate-method",
+            Self::Conflict => "conflict",
+            Self::Io => "io",
+        }
+    }
+
+    fn message(self, message: impl Display) -> String {
+        format!("solders-macros({}): {}", self.id(), message)
+    }
+}
+
+/// An error pointing at `tokens`.
+pub(crate) fn spanned(kind: ErrorKind, tokens: impl ToTokens, message: impl Display) -> syn::Error {
+    syn::Error::new_spanned(tokens, kind.message(message))
+}
+
+/// An error pointing at `span`.
+pub(crate) fn at(kind: ErrorKind, span: Span, message: impl Display) -> syn::Error {
+    syn::Error::new(span, kind.message(message))
+}
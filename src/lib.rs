@@ -3,16 +3,440 @@
 //!
 //! These macros make some very specific assumptions about the structs
 //! they're applied to, so they're unlikely to be useful for other projects.
+//!
+//! The macros that add methods to an impl block also work on trait impls, e.g.
+//! `#[pyhash] impl PyHash for Foo {}`. The methods then go in a new inherent `#[pymethods]`
+//! block, which needs pyo3's `multiple-pymethods` feature if the class has other
+//! `#[pymethods]` blocks.
+//!
+//! # Paths
+//!
+//! Generated code refers to pyo3 as `pyo3` and to solders types like `Signer` and `Pubkey`
+//! through `crate`. Every attribute macro accepts `crate = "..."` and `pyo3 = "..."` arguments
+//! to change these, e.g. `#[richcmp_signer(crate = "solders_traits")]` outside the main
+//! solders crate.
+//!
+//! The `pyo3 = "..."` rewrite applies to the macro's whole output, including the item it's
+//! applied to: paths starting with `pyo3::` in your own code change too.
+//!
+//! # Conditional methods
+//!
+//! Every attribute macro also accepts `cfg = <predicate>`, which puts everything it generates
+//! behind `#[cfg(<predicate>)]`, e.g. `#[json_file_methods(cfg = not(target_arch = "wasm32"))]`
+//! or `#[signer_methods(cfg = feature = "ring")]`. The item the macro is applied to is kept either way.
+//!
+#![cfg_attr(not(feature = "multiple-pymethods"), doc = "```rust")]
+#![cfg_attr(feature = "multiple-pymethods", doc = "```ignore")]
+//! use solders_macros::len_dunder;
+//!
+//! struct Foo(Vec<u8>);
+//!
+//! #[len_dunder(field = 0, cfg = all())]
+//! impl Foo {}
+//!
+//! assert_eq!(0, Foo(vec![]).__len__());
+//! ```
+//!
+#![cfg_attr(not(feature = "multiple-pymethods"), doc = "```compile_fail")]
+#![cfg_attr(feature = "multiple-pymethods", doc = "```ignore")]
+//! use solders_macros::len_dunder;
+//!
+//! struct Foo(Vec<u8>);
+//!
+//! #[len_dunder(field = 0, cfg = any())]
+//! impl Foo {}
+//!
+//! Foo(vec![]).__len__();
+//! ```
+//!
+//! # Features
+//!
+//! - `stubs`: `#[pyi_stub]` records `.pyi` stubs, assembled with `stub_writer!`.
+//! - `metadata`: the macros that add methods to impl blocks write a JSON description
+//!   of those methods to `$OUT_DIR/solders_metadata/<Class>.<macro>.<id>.json`, with an id
+//!   unique to the impl block. Files starting with `.` there are bookkeeping, not metadata.
+//!   The calling crate needs a build script for `OUT_DIR` to be set.
+//! - `bound`: generate code for pyo3's `Bound` API (pyo3 0.21+) instead of GIL refs.
+//!   Generated methods return `Bound<'py, PyBytes>` rather than `&'py PyBytes`, and
+//!   `__reduce__` takes `py` and returns `Bound` objects, so `CommonMethods::pybytes` and
+//!   `CommonMethods::pyreduce` must have matching signatures.
+//! - `wasm`: `common_methods`, `all_getters` and `inner_getters` also emit a `#[wasm_bindgen]`
+//!   impl block with the equivalent methods; see their docs. The calling crate needs
+//!   `wasm-bindgen`, and for `common_methods` also `bincode` and `serde_json`.
+//! - `bench`: `#[bench_methods]` generates criterion benchmarks of serialization round trips.
+//! - `multiple-pymethods`: instead of adding methods to the impl block they're applied to,
+//!   the macros put them in a new `#[pymethods]` block, so they can go above or below
+//!   `#[pymethods]`. Needs pyo3's `multiple-pymethods` feature. The doc examples that call
+//!   generated methods without pyo3 are ignored when this feature is on.
 use proc_macro::TokenStream;
-use proc_macro2::Ident;
-use quote::{quote, ToTokens};
-use syn::{parse_macro_input, ImplItem, ItemEnum, ItemImpl};
+use proc_macro2::{Ident, Span};
+use quote::{format_ident, quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_macro_input, ImplItem, ItemEnum, ItemImpl, Member, Type};
+
+mod args;
+mod bound;
+mod case;
+mod cfg;
+mod complex_enum;
+mod errors;
+mod metadata;
+mod out_dir;
+mod paths;
+mod registry;
+mod stubs;
+mod wasm;
+use args::MacroArgs;
+use case::RenameRule;
+use errors::ErrorKind;
+
+/// An item an attribute macro can be applied to.
+trait Target: Parse {
+    const DESCRIPTION: &'static str;
+}
+
+impl Target for ItemImpl {
+    const DESCRIPTION: &'static str = "impl blocks";
+}
+
+impl Target for ItemEnum {
+    const DESCRIPTION: &'static str = "enums";
+}
+
+impl Target for syn::ItemStruct {
+    const DESCRIPTION: &'static str = "structs";
+}
+
+impl Target for syn::ItemFn {
+    const DESCRIPTION: &'static str = "functions";
+}
+
+/// Parse the item an attribute macro is applied to, with a clear error for the wrong kind of item.
+fn parse_target<T: Target>(item: TokenStream, macro_name: &str) -> syn::Result<T> {
+    syn::parse::<T>(item.clone()).map_err(|e| match syn::parse::<syn::Item>(item) {
+        Ok(other) => errors::spanned(
+            ErrorKind::WrongItem,
+            other,
+            format!(
+                "`#[{}]` can only be applied to {}",
+                macro_name,
+                T::DESCRIPTION
+            ),
+        ),
+        Err(_) => e,
+    })
+}
+
+/// Like `parse_macro_input!`, but using `parse_target`.
+macro_rules! parse_target {
+    ($item:ident as $ty:ty, $macro_name:literal) => {
+        match parse_target::<$ty>($item, $macro_name) {
+            Ok(parsed) => parsed,
+            Err(e) => return e.to_compile_error().into(),
+        }
+    };
+}
+
+/// The name of the type an impl block is for, e.g. `Foo` in `impl Foo<T>`.
+fn self_type_ident(ast: &ItemImpl) -> syn::Result<Ident> {
+    match &*ast.self_ty {
+        Type::Path(p) if p.qself.is_none() => Ok(p.path.segments.last().unwrap().ident.clone()),
+        other => Err(errors::spanned(
+            ErrorKind::WrongItem,
+            other,
+            "expected a named type",
+        )),
+    }
+}
+
+/// Error if the impl block has generic parameters, for macros that generate a pyclass,
+/// pyfunction or class registration, none of which can be generic.
+fn reject_generics(ast: &ItemImpl, macro_name: &str) -> syn::Result<()> {
+    if ast.generics.params.is_empty() {
+        Ok(())
+    } else {
+        Err(errors::spanned(
+            ErrorKind::Generic,
+            &ast.generics,
+            format!("`{}` does not support generic impl blocks", macro_name),
+        ))
+    }
+}
+
+fn is_pymethods_attr(attr: &syn::Attribute) -> bool {
+    attr.path
+        .segments
+        .last()
+        .is_some_and(|seg| seg.ident == "pymethods")
+}
+
+/// Move a `#[pymethods]` attribute after the impl's other attributes, so any of our macros
+/// listed below it still run before it and the methods they add are registered.
+fn move_pymethods_last(attrs: &mut Vec<syn::Attribute>) {
+    if let Some(idx) = attrs.iter().position(is_pymethods_attr) {
+        let pymethods = attrs.remove(idx);
+        attrs.push(pymethods);
+    }
+}
+
+/// Remove generated methods whose name is already taken by a method in the impl block,
+/// returning an error for each that names the method and the macro.
+fn remove_duplicate_methods(ast: &mut ItemImpl, macro_name: &str) -> Vec<syn::Error> {
+    let existing: Vec<Ident> = ast
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Method(m) => Some(m.sig.ident.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut duplicates = Vec::new();
+    ast.items.retain(|item| {
+        let generated = match item {
+            ImplItem::Verbatim(tokens) => syn::parse2::<syn::ImplItemMethod>(tokens.clone()),
+            _ => return true,
+        };
+        let duplicate = generated
+            .ok()
+            .and_then(|m| existing.iter().find(|name| **name == m.sig.ident).cloned());
+        match duplicate {
+            Some(name) => {
+                duplicates.push(errors::spanned(
+                    ErrorKind::DuplicateMethod,
+                    &name,
+                    format!(
+                        "`{}` is already defined in this impl block, but `#[{}]` also generates it; \
+                         remove one of them",
+                        name, macro_name
+                    ),
+                ));
+                false
+            }
+            None => true,
+        }
+    });
+    duplicates
+}
+
+/// Error if the impl gets `__getnewargs__` and `__reduce__` but no `__reduce_ex__`.
+///
+/// pickle only calls `__getnewargs__` from the default `__reduce_ex__`, which defers to a
+/// `__reduce__` like the one `common_methods` adds, so the pickled arguments would be silently
+/// ignored. The `__reduce_ex__` of `pickle_state` does call `__getnewargs__`. Methods from our
+/// macros still to be expanded on the impl are counted too.
+fn reject_ignored_getnewargs(ast: &ItemImpl) -> syn::Result<()> {
+    let mut names: Vec<String> = ast
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Method(m) => Some(m.sig.ident.to_string()),
+            ImplItem::Verbatim(tokens) => syn::parse2::<syn::ImplItemMethod>(tokens.clone())
+                .ok()
+                .map(|m| m.sig.ident.to_string()),
+            _ => None,
+        })
+        .collect();
+    for attr in &ast.attrs {
+        let macro_name = match attr.path.segments.last() {
+            Some(seg) => seg.ident.to_string(),
+            None => continue,
+        };
+        let method = match macro_name.as_str() {
+            "common_methods" | "common_methods_rpc_resp" | "common_methods_rpc_resp_no_context" => {
+                "__reduce__"
+            }
+            "pickle_state" => "__reduce_ex__",
+            "getnewargs" => "__getnewargs__",
+            _ => continue,
+        };
+        names.push(method.to_owned());
+    }
+    let has = |name: &str| names.iter().any(|n| n == name);
+    if has("__getnewargs__") && has("__reduce__") && !has("__reduce_ex__") {
+        Err(errors::spanned(
+            ErrorKind::Conflict,
+            &ast.self_ty,
+            "pickle ignores `__getnewargs__` when the class defines `__reduce__`, as \
+             `common_methods` does; add `#[pickle_state]`, or use \
+             `#[common_methods(reduce = \"newargs\")]` instead of `#[getnewargs]`",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Names of the methods called as `self.name(...)` or `Self::name(...)` in `tokens`.
+fn called_methods(tokens: proc_macro2::TokenStream, names: &mut Vec<Ident>) {
+    use proc_macro2::TokenTree;
+    let trees: Vec<TokenTree> = tokens.into_iter().collect();
+    for (i, tree) in trees.iter().enumerate() {
+        if let TokenTree::Group(group) = tree {
+            called_methods(group.stream(), names);
+        }
+        let name = match &trees[i..] {
+            [TokenTree::Ident(recv), TokenTree::Punct(dot), TokenTree::Ident(name), TokenTree::Group(args), ..]
+                if recv == "self" && dot.as_char() == '.' =>
+            {
+                (args.delimiter() == proc_macro2::Delimiter::Parenthesis).then_some(name)
+            }
+            [TokenTree::Ident(recv), TokenTree::Punct(c1), TokenTree::Punct(c2), TokenTree::Ident(name), TokenTree::Group(args), ..]
+                if recv == "Self" && c1.as_char() == ':' && c2.as_char() == ':' =>
+            {
+                (args.delimiter() == proc_macro2::Delimiter::Parenthesis).then_some(name)
+            }
+            _ => None,
+        };
+        if let Some(name) = name {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+    }
+}
+
+/// Check that the methods the generated code relies on, like `pyhash` or `py_to_json`, exist.
+///
+/// Each becomes a `<Self>::name` path spanned at the macro attribute, so a missing provider
+/// is reported there by name instead of deep inside a generated method.
+/// Generic impls are skipped since the paths would need their parameters.
+fn provider_assertions(ast: &ItemImpl) -> proc_macro2::TokenStream {
+    if !ast.generics.params.is_empty() {
+        return proc_macro2::TokenStream::new();
+    }
+    let mut generated = Vec::new();
+    let mut called = Vec::new();
+    for item in &ast.items {
+        if let ImplItem::Verbatim(tokens) = item {
+            if let Ok(method) = syn::parse2::<syn::ImplItemMethod>(tokens.clone()) {
+                generated.push(method.sig.ident);
+            }
+            called_methods(tokens.clone(), &mut called);
+        }
+    }
+    // Conversion trait methods like `Self::from` have several impls to pick from, so a bare
+    // `<Self>::from` path is ambiguous.
+    let providers: Vec<&Ident> = called
+        .iter()
+        .filter(|name| !generated.contains(name) && *name != "from" && *name != "try_from")
+        .collect();
+    if providers.is_empty() {
+        return proc_macro2::TokenStream::new();
+    }
+    let self_ty = &ast.self_ty;
+    quote! {
+        const _: fn() = || {
+            #(let _ = <#self_ty>::#providers;)*
+        };
+    }
+}
+
+/// Give every token the location of `span`, keeping its name resolution.
+fn relocate(tokens: proc_macro2::TokenStream, span: Span) -> proc_macro2::TokenStream {
+    use proc_macro2::TokenTree;
+    tokens
+        .into_iter()
+        .map(|mut tree| {
+            if let TokenTree::Group(group) = &tree {
+                let mut new_group =
+                    proc_macro2::Group::new(group.delimiter(), relocate(group.stream(), span));
+                new_group.set_span(group.span().located_at(span));
+                tree = TokenTree::Group(new_group);
+            } else {
+                tree.set_span(tree.span().located_at(span));
+            }
+            tree
+        })
+        .collect()
+}
+
+/// Point the generated methods at the impl's self type, so compile errors inside them are
+/// reported on the user's impl block. rustc still notes which attribute macro they came from.
+fn relocate_generated(ast: &mut ItemImpl) {
+    let span = match ast.self_ty.to_token_stream().into_iter().next() {
+        Some(tree) => tree.span(),
+        None => return,
+    };
+    for item in &mut ast.items {
+        if let ImplItem::Verbatim(tokens) = item {
+            *tokens = relocate(std::mem::take(tokens), span);
+        }
+    }
+}
+
+/// The output of an impl-block macro, recording the methods it added with the `metadata` feature.
+///
+/// Generated methods that clash with existing ones are dropped in favour of an error;
+/// see `remove_duplicate_methods`.
+///
+/// A `#[pymethods]` attribute still to be expanded is moved to run last; see `move_pymethods_last`.
+///
+/// With the `multiple-pymethods` feature the added methods go in a new `#[pymethods]` block
+/// and the original block is left as it was. The same happens for trait impls, since the
+/// added methods aren't trait items.
+fn impl_output(macro_name: &str, ast: &ItemImpl) -> proc_macro2::TokenStream {
+    let mut ast = ast.clone();
+    move_pymethods_last(&mut ast.attrs);
+    let duplicates = remove_duplicate_methods(&mut ast, macro_name);
+    relocate_generated(&mut ast);
+    let ast = &ast;
+    let mut tokens = if cfg!(feature = "multiple-pymethods") || ast.trait_.is_some() {
+        let (added, original): (Vec<ImplItem>, Vec<ImplItem>) = ast
+            .items
+            .iter()
+            .cloned()
+            .partition(|item| matches!(item, ImplItem::Verbatim(_)));
+        let (impl_generics, _, where_clause) = ast.generics.split_for_impl();
+        let self_ty = &ast.self_ty;
+        let original = ItemImpl {
+            items: original,
+            ..ast.clone()
+        };
+        quote! {
+            #original
+
+            #[pyo3::pymethods]
+            impl #impl_generics #self_ty #where_clause {
+                #(#added)*
+            }
+        }
+    } else {
+        ast.to_token_stream()
+    };
+    if cfg!(feature = "metadata") {
+        if let Err(e) = metadata::record(macro_name, ast) {
+            tokens.extend(e.to_compile_error());
+        }
+    }
+    tokens.extend(provider_assertions(ast));
+    tokens.extend(duplicates.iter().map(syn::Error::to_compile_error));
+    tokens
+}
+
+struct PyhashArgs {
+    from_bytes: bool,
+}
+
+impl Parse for PyhashArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            from_bytes: args.flag("from_bytes")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
 
 /// Add a `__hash__` to the impl using the `PyHash` trait.
 ///
+/// With `#[pyhash(from_bytes)]` the hash is instead computed from the serialized bytes
+/// returned by `CommonMethods::pybytes`, for types that have `__bytes__` but no
+/// meaningful `Hash` impl. Objects with equal bytes then always hash equally.
+///
 /// # Example
 ///
-/// ```rust
+#[cfg_attr(not(feature = "multiple-pymethods"), doc = "```rust")]
+#[cfg_attr(feature = "multiple-pymethods", doc = "```ignore")]
 /// use solders_macros::pyhash;
 ///
 /// #[derive(Debug)]
@@ -30,20 +454,40 @@ use syn::{parse_macro_input, ImplItem, ItemEnum, ItemImpl};
 ///
 /// ```
 #[proc_macro_attribute]
-pub fn pyhash(_: TokenStream, item: TokenStream) -> TokenStream {
-    let mut ast = parse_macro_input!(item as ItemImpl);
-    let to_add = quote! {pub fn __hash__(&self) -> u64 {self.pyhash()}};
-    ast.items.push(ImplItem::Verbatim(to_add));
-    TokenStream::from(ast.to_token_stream())
+pub fn pyhash(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let PyhashArgs { from_bytes } = parse_macro_input!(args as PyhashArgs);
+        let mut ast = parse_target!(item as ItemImpl, "pyhash");
+        let to_add = if from_bytes {
+            let as_bytes = bound::as_bytes(quote! { bytes });
+            quote! {
+                pub fn __hash__(&self) -> u64 {
+                    use std::hash::{Hash, Hasher};
+                    pyo3::prelude::Python::with_gil(|py| {
+                        let bytes = self.pybytes(py);
+                        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                        Hash::hash(#as_bytes, &mut hasher);
+                        hasher.finish()
+                    })
+                }
+            }
+        } else {
+            quote! {pub fn __hash__(&self) -> u64 {self.pyhash()}}
+        };
+        ast.items.push(ImplItem::Verbatim(to_add));
+        TokenStream::from(impl_output("pyhash", &ast))
+    })
 }
 
 /// Add a `__richcmp__` to the impl using the `RichcmpFull` trait.
 ///
 /// # Example
 ///
-/// ```rust
+#[cfg_attr(not(feature = "multiple-pymethods"), doc = "```rust")]
+#[cfg_attr(feature = "multiple-pymethods", doc = "```ignore")]
 /// use solders_macros::richcmp_full;
-///
+/// # mod pyo3 { pub mod basic { pub enum CompareOp { Eq } } }
+/// # use pyo3::basic::CompareOp;
 ///
 /// #[derive(Debug)]
 /// struct Foo(u8);
@@ -60,186 +504,756 @@ pub fn pyhash(_: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// ```
 #[proc_macro_attribute]
-pub fn richcmp_full(_: TokenStream, item: TokenStream) -> TokenStream {
-    let mut ast = parse_macro_input!(item as ItemImpl);
-    let to_add = quote! {pub fn __richcmp__(&self, other: &Self, op: pyo3::basic::CompareOp) -> bool {self.richcmp(other, op)}};
-    ast.items.push(ImplItem::Verbatim(to_add));
-    TokenStream::from(ast.to_token_stream())
+pub fn richcmp_full(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "richcmp_full");
+        let to_add = quote! {pub fn __richcmp__(&self, other: &Self, op: pyo3::basic::CompareOp) -> bool {self.richcmp(other, op)}};
+        ast.items.push(ImplItem::Verbatim(to_add));
+        TokenStream::from(impl_output("richcmp_full", &ast))
+    })
 }
 
 /// Add a `__richcmp__` to the impl using the `RichcmpEqualityOnly` trait.
 #[proc_macro_attribute]
-pub fn richcmp_eq_only(_: TokenStream, item: TokenStream) -> TokenStream {
-    let mut ast = parse_macro_input!(item as ItemImpl);
-    let to_add = quote! {pub fn __richcmp__(&self, other: &Self, op: pyo3::basic::CompareOp) -> pyo3::prelude::PyResult<bool> {self.richcmp(other, op)}};
-    ast.items.push(ImplItem::Verbatim(to_add));
-    TokenStream::from(ast.to_token_stream())
+pub fn richcmp_eq_only(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "richcmp_eq_only");
+        let to_add = quote! {pub fn __richcmp__(&self, other: &Self, op: pyo3::basic::CompareOp) -> pyo3::prelude::PyResult<bool> {self.richcmp(other, op)}};
+        ast.items.push(ImplItem::Verbatim(to_add));
+        TokenStream::from(impl_output("richcmp_eq_only", &ast))
+    })
 }
 
 /// Add a `__richcmp__` to the impl using the `RichcmpSigner` trait.
 #[proc_macro_attribute]
-pub fn richcmp_signer(_: TokenStream, item: TokenStream) -> TokenStream {
-    let mut ast = parse_macro_input!(item as ItemImpl);
-    let to_add = quote! {pub fn __richcmp__(&self, other: crate::Signer, op: pyo3::basic::CompareOp) -> pyo3::prelude::PyResult<bool> {self.richcmp(other, op)}};
-    ast.items.push(ImplItem::Verbatim(to_add));
-    TokenStream::from(ast.to_token_stream())
+pub fn richcmp_signer(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "richcmp_signer");
+        let to_add = quote! {pub fn __richcmp__(&self, other: __solders_crate::Signer, op: pyo3::basic::CompareOp) -> pyo3::prelude::PyResult<bool> {self.richcmp(other, op)}};
+        ast.items.push(ImplItem::Verbatim(to_add));
+        TokenStream::from(impl_output("richcmp_signer", &ast))
+    })
+}
+
+fn clone_method_item() -> ImplItem {
+    ImplItem::Verbatim(quote! {
+        /// Create a copy of this object.
+        ///
+        /// Returns:
+        ///     A new object equal to this one.
+        ///
+        #[pyo3(name = "clone")]
+        pub fn py_clone(&self) -> Self {
+            Clone::clone(self)
+        }
+    })
+}
+
+/// Whether the impl already defines the Python `clone` method, as `clone` or `py_clone`.
+fn defines_clone(ast: &ItemImpl) -> bool {
+    ast.items.iter().any(|item| match item {
+        ImplItem::Method(m) => m.sig.ident == "clone" || m.sig.ident == "py_clone",
+        _ => false,
+    })
+}
+
+/// Add a Python `clone` method using the `Clone` trait.
+///
+/// The Rust method is named `py_clone`, so it doesn't shadow `Clone::clone`.
+/// `common_methods` and the `common_methods_rpc_resp*` macros already add this.
+#[proc_macro_attribute]
+pub fn clone_method(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "clone_method");
+        ast.items.push(clone_method_item());
+        TokenStream::from(impl_output("clone_method", &ast))
+    })
+}
+
+/// Add `__copy__` and `__deepcopy__` using the `Clone` trait.
+#[proc_macro_attribute]
+pub fn copy_methods(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "copy_methods");
+        let memo = bound::arg(quote! { PyDict });
+        let methods = vec![
+            ImplItem::Verbatim(quote! { pub fn __copy__(&self) -> Self {self.clone()} }),
+            ImplItem::Verbatim(
+                quote! { pub fn __deepcopy__(&self, _memo: #memo) -> Self {self.clone()} },
+            ),
+        ];
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("copy_methods", &ast))
+    })
+}
+
+enum ReduceStrategy {
+    Bytes,
+    NewArgs,
+}
+
+struct CommonMethodsArgs {
+    reduce: ReduceStrategy,
+    register: Option<syn::LitStr>,
+    gen_tests: bool,
+    sample: Option<Type>,
+}
+
+impl Parse for CommonMethodsArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let reduce = match args.lit_str("reduce")? {
+            None => ReduceStrategy::Bytes,
+            Some(lit) => match lit.value().as_str() {
+                "bytes" => ReduceStrategy::Bytes,
+                "newargs" => ReduceStrategy::NewArgs,
+                _ => {
+                    return Err(errors::spanned(
+                        ErrorKind::InvalidArgument,
+                        lit,
+                        "expected `reduce = \"bytes\"` or `reduce = \"newargs\"`",
+                    ))
+                }
+            },
+        };
+        let register = args.lit_str("register")?;
+        let gen_tests = args.flag("gen_tests")?;
+        let sample = args.ty("sample")?;
+        if sample.is_some() && !gen_tests {
+            return Err(errors::at(
+                ErrorKind::InvalidArgument,
+                Span::call_site(),
+                "`sample` is only used with `gen_tests`",
+            ));
+        }
+        args.finish()?;
+        Ok(Self {
+            reduce,
+            register,
+            gen_tests,
+            sample,
+        })
+    }
 }
 
 /// Add `__bytes__`, `__str__`, `__repr__` and `__reduce__`, `to_json` and `from_json` using the `CommonMethods` trait.
 ///
-/// Also add `from_bytes` if not already defined.
+/// Also add `from_bytes` and `clone` if not already defined.
+///
+/// By default `__reduce__` delegates to `CommonMethods::pyreduce`.
+/// With `#[common_methods(reduce = "newargs")]` it instead returns the class and
+/// `(bytes(self),)`, so unpickling just calls the constructor with the serialized bytes.
+/// This is smaller and faster for types like `Pubkey` whose `#[new]` takes bytes.
+///
+/// With `#[common_methods(register = "submodule")]` the class is also recorded in the
+/// class registry; see `class_registry!`.
+///
+/// With `#[common_methods(gen_tests)]` it also generates `#[cfg(test)]` tests checking that
+/// the object survives a round trip through bytes and through JSON. The tests use
+/// `Default::default()` as the sample object, or the function given with
+/// `sample = path::to_fn`. The type must implement `PartialEq` and `Debug`.
+///
+/// With the `wasm` feature it also adds a `#[wasm_bindgen]` impl block with `toBytes`,
+/// `fromBytes`, `toJson` and `fromJson`, using bincode and serde_json. Their errors are
+/// thrown in JavaScript as the error message.
 #[proc_macro_attribute]
-pub fn common_methods(_: TokenStream, item: TokenStream) -> TokenStream {
-    let mut ast = parse_macro_input!(item as ItemImpl);
-    let mut methods = vec![
-        ImplItem::Verbatim(
-            quote! {pub fn __bytes__<'a>(&self, py: pyo3::prelude::Python<'a>) -> &'a pyo3::types::PyBytes  {self.pybytes(py)}},
-        ),
-        ImplItem::Verbatim(quote! { pub fn __str__(&self) -> String {self.pystr()} }),
-        ImplItem::Verbatim(quote! { pub fn __repr__(&self) -> String {self.pyrepr()} }),
-        ImplItem::Verbatim(
-            quote! { pub fn __reduce__(&self) -> pyo3::prelude::PyResult<(pyo3::prelude::PyObject, pyo3::prelude::PyObject)> {self.pyreduce()} },
-        ),
-        ImplItem::Verbatim(quote! {
-        /// Convert to a JSON string.
-        pub fn to_json(&self) -> String {self.py_to_json()} }),
-        ImplItem::Verbatim(quote! {
-        /// Build from a JSON string.
-        #[staticmethod] pub fn from_json(raw: &str) -> PyResult<Self> {Self::py_from_json(raw)} }),
-    ];
-    if !ast.items.iter().any(|item| match item {
-        ImplItem::Method(m) => m.sig.ident == "from_bytes",
+pub fn common_methods(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let CommonMethodsArgs {
+            reduce,
+            register,
+            gen_tests,
+            sample,
+        } = parse_macro_input!(args as CommonMethodsArgs);
+        let mut ast = parse_target!(item as ItemImpl, "common_methods");
+        let reduce_output = bound::reduce_output();
+        let reduce_method = match reduce {
+            ReduceStrategy::Bytes => bound::reduce_method(),
+            ReduceStrategy::NewArgs if cfg!(feature = "bound") => ImplItem::Verbatim(quote! {
+                pub fn __reduce__<'a>(&self, py: pyo3::prelude::Python<'a>) -> pyo3::prelude::PyResult<#reduce_output> {
+                    let constructor = py.get_type_bound::<Self>().into_any();
+                    let args = pyo3::types::PyTuple::new_bound(py, [self.pybytes(py)]).into_any();
+                    Ok((constructor, args))
+                }
+            }),
+            ReduceStrategy::NewArgs => ImplItem::Verbatim(quote! {
+                pub fn __reduce__(&self) -> pyo3::prelude::PyResult<(pyo3::prelude::PyObject, pyo3::prelude::PyObject)> {
+                    use pyo3::prelude::IntoPy;
+                    pyo3::prelude::Python::with_gil(|py| {
+                        let constructor = py.get_type::<Self>().into_py(py);
+                        Ok((constructor, (self.pybytes(py),).into_py(py)))
+                    })
+                }
+            }),
+        };
+        let mut methods = vec![
+            bound::bytes_method(),
+            ImplItem::Verbatim(quote! { pub fn __str__(&self) -> String {self.pystr()} }),
+            ImplItem::Verbatim(quote! { pub fn __repr__(&self) -> String {self.pyrepr()} }),
+            reduce_method,
+            ImplItem::Verbatim(quote! {
+            /// Convert to a JSON string.
+            pub fn to_json(&self) -> String {self.py_to_json()} }),
+            ImplItem::Verbatim(quote! {
+            /// Build from a JSON string.
+            #[staticmethod] pub fn from_json(raw: &str) -> pyo3::prelude::PyResult<Self> {Self::py_from_json(raw)} }),
+        ];
+        if !ast.items.iter().any(|item| match item {
+            ImplItem::Method(m) => m.sig.ident == "from_bytes",
+            _ => false,
+        }) {
+            let from_bytes = ImplItem::Verbatim(quote! {
+                /// Deserialize from bytes.
+                ///
+                /// Args:
+                ///     data (bytes): the serialized object.
+                ///
+                /// Returns: the deserialized object.
+                ///
+                #[staticmethod]
+                pub fn from_bytes(data: &[u8]) -> pyo3::prelude::PyResult<Self> {
+                    Self::py_from_bytes(data)
+                }
+            });
+            methods.push(from_bytes);
+        };
+        if !defines_clone(&ast) {
+            methods.push(clone_method_item());
+        };
+        ast.items.extend_from_slice(&methods);
+        if let Err(e) = reject_ignored_getnewargs(&ast) {
+            return e.to_compile_error().into();
+        }
+        let mut new_stream = impl_output("common_methods", &ast);
+        if cfg!(feature = "wasm") {
+            let methods = wasm::serialization_methods();
+            match wasm::impl_block(&ast.self_ty, &ast.generics, "common_methods", &methods) {
+                Ok(block) => new_stream.extend(block),
+                Err(e) => new_stream.extend(e.to_compile_error()),
+            }
+        }
+        if let Some(submodule) = register {
+            match reject_generics(&ast, "common_methods(register = ...)") {
+                Ok(()) => new_stream.extend(registry::registration(&ast.self_ty, &submodule)),
+                Err(e) => new_stream.extend(e.to_compile_error()),
+            }
+        }
+        if gen_tests {
+            match round_trip_tests(&ast, sample) {
+                Ok(tests) => new_stream.extend(tests),
+                Err(e) => new_stream.extend(e.to_compile_error()),
+            }
+        }
+        TokenStream::from(new_stream)
+    })
+}
+
+/// The expression building a sample object: a call to `sample`, or `Default::default()`.
+fn sample_expr(self_ty: &Type, sample: Option<Type>) -> proc_macro2::TokenStream {
+    match sample {
+        Some(sample) => quote! { #sample() },
+        None => quote! { <#self_ty as Default>::default() },
+    }
+}
+
+/// Tests that a sample object survives `CommonMethods` bytes and JSON round trips.
+fn round_trip_tests(ast: &ItemImpl, sample: Option<Type>) -> syn::Result<proc_macro2::TokenStream> {
+    reject_generics(ast, "common_methods(gen_tests)")?;
+    let name = self_type_ident(ast)?;
+    let self_ty = &ast.self_ty;
+    let mod_name = format_ident!(
+        "{}_round_trip_tests",
+        RenameRule::SnakeCase.apply(&name.to_string())
+    );
+    let sample = sample_expr(self_ty, sample);
+    let as_bytes = bound::as_bytes(quote! { bytes });
+    Ok(quote! {
+        #[cfg(test)]
+        mod #mod_name {
+            use super::*;
+
+            #[test]
+            fn bytes_round_trip() {
+                let original: #self_ty = #sample;
+                pyo3::prelude::Python::with_gil(|py| {
+                    let bytes = original.pybytes(py);
+                    let parsed = <#self_ty>::py_from_bytes(#as_bytes).unwrap();
+                    assert_eq!(parsed, original);
+                });
+            }
+
+            #[test]
+            fn json_round_trip() {
+                let original: #self_ty = #sample;
+                let parsed = <#self_ty>::py_from_json(&original.py_to_json()).unwrap();
+                assert_eq!(parsed, original);
+            }
+        }
+    })
+}
+
+struct BenchArgs {
+    sample: Option<Type>,
+}
+
+impl Parse for BenchArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            sample: args.ty("sample")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Generate criterion benchmarks of the `CommonMethods` serialization paths.
+/// Only does anything with the `bench` feature enabled.
+///
+/// Defines `pub fn bench_<snake_case_name>(c: &mut criterion::Criterion)`, next to the impl block,
+/// benchmarking `to_json`, `from_json` and a `__bytes__`/`from_bytes` round trip in a
+/// benchmark group named after the class. Like `#[common_methods(gen_tests)]`, the sample
+/// object is `Default::default()` or the result of the function given with `sample = path::to_fn`.
+/// The calling crate needs `criterion` as a dependency when the feature is on.
+///
+/// # Example
+///
+/// ```ignore
+/// #[bench_methods(sample = Pubkey::new_unique)]
+/// #[common_methods]
+/// #[pymethods]
+/// impl Pubkey {}
+///
+/// // benches/serialization.rs
+/// criterion_group!(benches, solders::pubkey::bench_pubkey);
+/// criterion_main!(benches);
+/// ```
+#[proc_macro_attribute]
+pub fn bench_methods(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let BenchArgs { sample } = parse_macro_input!(args as BenchArgs);
+        let ast = parse_target!(item as ItemImpl, "bench_methods");
+        let mut new_stream = ast.to_token_stream();
+        if cfg!(feature = "bench") {
+            match serialization_benches(&ast, sample) {
+                Ok(benches) => new_stream.extend(benches),
+                Err(e) => new_stream.extend(e.to_compile_error()),
+            }
+        }
+        TokenStream::from(new_stream)
+    })
+}
+
+/// A criterion benchmark function for the `CommonMethods` JSON and bytes round trips.
+fn serialization_benches(
+    ast: &ItemImpl,
+    sample: Option<Type>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    reject_generics(ast, "bench_methods")?;
+    let name = self_type_ident(ast)?;
+    let self_ty = &ast.self_ty;
+    let fn_name = format_ident!("bench_{}", RenameRule::SnakeCase.apply(&name.to_string()));
+    let group_name = name.to_string();
+    let sample = sample_expr(self_ty, sample);
+    let as_bytes = bound::as_bytes(quote! { bytes });
+    Ok(quote! {
+        /// Benchmark JSON and bytes serialization round trips.
+        pub fn #fn_name(c: &mut criterion::Criterion) {
+            let original: #self_ty = #sample;
+            let json = original.py_to_json();
+            let mut group = c.benchmark_group(#group_name);
+            group.bench_function("to_json", |b| {
+                b.iter(|| criterion::black_box(&original).py_to_json())
+            });
+            group.bench_function("from_json", |b| {
+                b.iter(|| <#self_ty>::py_from_json(criterion::black_box(&json)).unwrap())
+            });
+            group.bench_function("bytes_round_trip", |b| {
+                pyo3::prelude::Python::with_gil(|py| {
+                    b.iter(|| {
+                        let bytes = criterion::black_box(&original).pybytes(py);
+                        <#self_ty>::py_from_bytes(#as_bytes).unwrap()
+                    })
+                })
+            });
+            group.finish();
+        }
+    })
+}
+
+struct PickleStateArgs {
+    frozen: bool,
+}
+
+impl Parse for PickleStateArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            frozen: args.flag("frozen")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Add `__getstate__`, `__setstate__` and a `__reduce_ex__` that pickles the object's state,
+/// using the `CommonMethods` trait.
+///
+/// The pickled state is the object's serialized bytes. Unpickling creates the object with
+/// `copyreg.__newobj__`, i.e. `type(obj).__new__`, so instances of Python subclasses come
+/// back as the subclass. `__new__` gets the arguments from `__getnewargs__` if the class has
+/// one (see `getnewargs`) and none otherwise, and `__setstate__` then replaces the object's
+/// value with the deserialized state.
+///
+/// `#[pyclass(frozen)]` objects can't be changed after creation, so for those pass
+/// `#[pickle_state(frozen)]`: there's no `__setstate__`, and `__new__` is called with the state
+/// bytes as its only argument, so the class's `#[new]` must accept them.
+///
+/// Pickle prefers `__reduce_ex__` over `__reduce__`, so this takes over from the `__reduce__`
+/// added by `common_methods`. Don't combine it with `pickle_buffer`, which also adds `__reduce_ex__`.
+#[proc_macro_attribute]
+pub fn pickle_state(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let PickleStateArgs { frozen } = parse_macro_input!(args as PickleStateArgs);
+        let mut ast = parse_target!(item as ItemImpl, "pickle_state");
+        let py_bytes = bound::py_bytes();
+        let slf = if cfg!(feature = "bound") {
+            quote! { slf: &pyo3::Bound<'_, Self> }
+        } else {
+            quote! { slf: &pyo3::PyCell<Self> }
+        };
+        let methods_in_scope = bound::methods_in_scope();
+        let import_copyreg = bound::import(quote! { py }, "copyreg");
+        let new_tuple = bound::new(quote! { PyTuple });
+        let reduce_ex = if frozen {
+            quote! {
+                pub fn __reduce_ex__(#slf, py: pyo3::prelude::Python<'_>, _protocol: u8) -> pyo3::prelude::PyResult<pyo3::prelude::PyObject> {
+                    #methods_in_scope
+                    use pyo3::prelude::IntoPy;
+                    let newobj = #import_copyreg?.getattr("__newobj__")?;
+                    let state = slf.borrow().__getstate__(py);
+                    let args: [pyo3::prelude::PyObject; 2] = [slf.get_type().into_py(py), state.into_py(py)];
+                    Ok((newobj, #new_tuple(py, args)).into_py(py))
+                }
+            }
+        } else {
+            quote! {
+                pub fn __reduce_ex__(#slf, py: pyo3::prelude::Python<'_>, _protocol: u8) -> pyo3::prelude::PyResult<pyo3::prelude::PyObject> {
+                    #methods_in_scope
+                    use pyo3::prelude::IntoPy;
+                    let newobj = #import_copyreg?.getattr("__newobj__")?;
+                    let mut args: Vec<pyo3::prelude::PyObject> = vec![slf.get_type().into_py(py)];
+                    if let Ok(getnewargs) = slf.getattr("__getnewargs__") {
+                        let newargs = getnewargs.call0()?;
+                        let newargs = newargs.downcast::<pyo3::types::PyTuple>()?;
+                        args.extend(newargs.iter().map(|arg| arg.into_py(py)));
+                    }
+                    let state = slf.borrow().__getstate__(py);
+                    Ok((newobj, #new_tuple(py, args), state).into_py(py))
+                }
+            }
+        };
+        let mut methods = vec![
+            ImplItem::Verbatim(
+                quote! {pub fn __getstate__<'a>(&self, py: pyo3::prelude::Python<'a>) -> #py_bytes {self.pybytes(py)}},
+            ),
+            ImplItem::Verbatim(reduce_ex),
+        ];
+        if !frozen {
+            methods.push(ImplItem::Verbatim(quote! {
+                pub fn __setstate__(&mut self, state: &[u8]) -> pyo3::prelude::PyResult<()> {
+                    *self = Self::py_from_bytes(state)?;
+                    Ok(())
+                }
+            }));
+        }
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("pickle_state", &ast))
+    })
+}
+
+/// Add a `__reduce_ex__` that supports out-of-band pickling, using the `CommonMethods` trait.
+///
+/// For pickle protocol 5 and above the serialized bytes are wrapped in a `pickle.PickleBuffer`,
+/// so they can be handed to a `buffer_callback` without being copied into the pickle stream.
+/// Older protocols fall back to `CommonMethods::pyreduce`.
+///
+/// Also adds a hidden `_from_pickle_buffer` staticmethod that unpickling calls.
+#[proc_macro_attribute]
+pub fn pickle_buffer(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "pickle_buffer");
+        let reduce_output = bound::reduce_output();
+        let data = bound::arg(quote! { PyAny });
+        let buffer = bound::buffer(quote! { data });
+        let methods_in_scope = bound::methods_in_scope();
+        let reduce_ex = if cfg!(feature = "bound") {
+            ImplItem::Verbatim(quote! {
+                pub fn __reduce_ex__<'a>(&self, py: pyo3::prelude::Python<'a>, protocol: u8) -> pyo3::prelude::PyResult<#reduce_output> {
+                    #methods_in_scope
+                    if protocol < 5 {
+                        return self.pyreduce(py);
+                    }
+                    let buffer = py.import_bound("pickle")?.getattr("PickleBuffer")?.call1((self.pybytes(py),))?;
+                    let constructor = py.get_type_bound::<Self>().getattr("_from_pickle_buffer")?;
+                    Ok((constructor, pyo3::types::PyTuple::new_bound(py, [buffer]).into_any()))
+                }
+            })
+        } else {
+            ImplItem::Verbatim(quote! {
+                pub fn __reduce_ex__(&self, py: pyo3::prelude::Python<'_>, protocol: u8) -> pyo3::prelude::PyResult<(pyo3::prelude::PyObject, pyo3::prelude::PyObject)> {
+                    use pyo3::prelude::IntoPy;
+                    if protocol < 5 {
+                        return self.pyreduce();
+                    }
+                    let buffer = py.import("pickle")?.getattr("PickleBuffer")?.call1((self.pybytes(py),))?;
+                    let constructor = py.get_type::<Self>().getattr("_from_pickle_buffer")?;
+                    Ok((constructor.into_py(py), (buffer,).into_py(py)))
+                }
+            })
+        };
+        let methods = vec![
+            reduce_ex,
+            ImplItem::Verbatim(quote! {
+                #[doc(hidden)]
+                #[staticmethod]
+                pub fn _from_pickle_buffer(py: pyo3::prelude::Python<'_>, data: #data) -> pyo3::prelude::PyResult<Self> {
+                    let buffer = #buffer?;
+                    Self::py_from_bytes(&buffer.to_vec(py)?)
+                }
+            }),
+        ];
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("pickle_buffer", &ast))
+    })
+}
+
+fn is_python_token(ty: &Type) -> bool {
+    match ty {
+        Type::Path(p) => p
+            .path
+            .segments
+            .last()
+            .is_some_and(|seg| seg.ident == "Python"),
         _ => false,
-    }) {
-        let from_bytes = ImplItem::Verbatim(quote! {
-            /// Deserialize from bytes.
-            ///
-            /// Args:
-            ///     data (bytes): the serialized object.
-            ///
-            /// Returns: the deserialized object.
-            ///
-            #[staticmethod]
-            pub fn from_bytes(data: &[u8]) -> PyResult<Self> {
-                Self::py_from_bytes(data)
+    }
+}
+
+/// Add `__getnewargs__` and `__getnewargs_ex__` based on the `#[new]` constructor in the impl.
+///
+/// Each constructor parameter is assumed to have a getter method of the same name,
+/// so the constructor and the pickled arguments can't drift apart. A missing one, e.g. for a
+/// `#[pyo3(get)]` field, is reported at the parameter. A `py: Python` parameter is skipped.
+///
+/// pickle ignores `__getnewargs__` if the class defines `__reduce__` without `__reduce_ex__`,
+/// so combining this with `common_methods` on the same impl is an error unless `pickle_state`
+/// is also used.
+#[proc_macro_attribute]
+pub fn getnewargs(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "getnewargs");
+        let constructor = ast.items.iter().find_map(|item| match item {
+            ImplItem::Method(m) if m.attrs.iter().any(|a| a.path.is_ident("new")) => Some(m),
+            _ => None,
+        });
+        let constructor = match constructor {
+            Some(c) => c,
+            None => {
+                return errors::spanned(
+                    ErrorKind::WrongItem,
+                    &ast.self_ty,
+                    "no `#[new]` method found in this impl",
+                )
+                .to_compile_error()
+                .into()
+            }
+        };
+        let mut getters = Vec::new();
+        for arg in &constructor.sig.inputs {
+            if let syn::FnArg::Typed(pat_type) = arg {
+                if is_python_token(&pat_type.ty) {
+                    continue;
+                }
+                match &*pat_type.pat {
+                    syn::Pat::Ident(pat_ident) => getters.push(pat_ident.ident.clone()),
+                    other => {
+                        return errors::spanned(
+                            ErrorKind::InvalidArgument,
+                            other,
+                            "expected a plain parameter name",
+                        )
+                        .to_compile_error()
+                        .into()
+                    }
+                }
+            }
+        }
+        let py_tuple = bound::owned(quote! { PyTuple });
+        let py_dict = bound::owned(quote! { PyDict });
+        let new_tuple = bound::new(quote! { PyTuple });
+        let new_dict = bound::new(quote! { PyDict });
+        let methods = vec![
+            ImplItem::Verbatim(quote! {
+                pub fn __getnewargs__<'a>(&self, py: pyo3::prelude::Python<'a>) -> #py_tuple {
+                    #new_tuple(py, self._getnewargs_values(py))
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                pub fn __getnewargs_ex__<'a>(&self, py: pyo3::prelude::Python<'a>) -> (#py_tuple, #py_dict) {
+                    (self.__getnewargs__(py), #new_dict(py))
+                }
+            }),
+        ];
+        ast.items.extend_from_slice(&methods);
+        if let Err(e) = reject_ignored_getnewargs(&ast) {
+            return e.to_compile_error().into();
+        }
+        // The getter calls go in a separate, plain impl that isn't relocated to the self type
+        // like the generated methods, so a missing getter is reported at its parameter.
+        let calls = getters.iter().map(|getter| {
+            quote::quote_spanned! {getter.span()=>
+                pyo3::prelude::IntoPy::into_py(self.#getter(), py)
             }
         });
-        methods.push(from_bytes);
-    };
-    ast.items.extend_from_slice(&methods);
-    TokenStream::from(ast.to_token_stream())
+        let (impl_generics, _, where_clause) = ast.generics.split_for_impl();
+        let self_ty = &ast.self_ty;
+        let mut new_stream = impl_output("getnewargs", &ast);
+        new_stream.extend(quote! {
+            impl #impl_generics #self_ty #where_clause {
+                #[doc(hidden)]
+                fn _getnewargs_values(&self, py: pyo3::prelude::Python<'_>) -> Vec<pyo3::prelude::PyObject> {
+                    vec![#(#calls),*]
+                }
+            }
+        });
+        TokenStream::from(new_stream)
+    })
 }
 
 /// Add `__bytes__`, `__str__`, `__repr__`, `__reduce__`, `to_json`, `from_json`, `from_bytes` and `__richcmp__` using the `CommonMethodsRpcResp` trait.
+///
+/// Also add `clone` if not already defined.
 #[proc_macro_attribute]
-pub fn common_methods_rpc_resp(_: TokenStream, item: TokenStream) -> TokenStream {
-    let mut ast = parse_macro_input!(item as ItemImpl);
-    let methods = vec![
-        ImplItem::Verbatim(
-            quote! {pub fn __bytes__<'a>(&self, py: pyo3::prelude::Python<'a>) -> &'a pyo3::types::PyBytes  {self.pybytes(py)}},
-        ),
-        ImplItem::Verbatim(quote! { pub fn __str__(&self) -> String {self.pystr()} }),
-        ImplItem::Verbatim(quote! { pub fn __repr__(&self) -> String {self.pyrepr()} }),
-        ImplItem::Verbatim(
-            quote! { pub fn __reduce__(&self) -> pyo3::prelude::PyResult<(pyo3::prelude::PyObject, pyo3::prelude::PyObject)> {self.pyreduce()} },
-        ),
-        ImplItem::Verbatim(quote! {
-        /// Convert to a JSON string.
-        pub fn to_json(&self) -> String {self.py_to_json()} }),
-        ImplItem::Verbatim(quote! {
-        /// Build from a JSON string.
-        ///
-        /// Args:
-        ///     raw (str): The RPC JSON response (can be an error response).
-        ///
-        /// Returns:
-        ///     Either the deserialized object or ``RPCError``.
-        ///
-        #[staticmethod]
-        pub fn from_json(raw: &str) -> PyResult<crate::rpc::responses::Resp<Self>> {Self::py_from_json(raw)} }),
-        ImplItem::Verbatim(quote! {
-            /// Deserialize from bytes.
+pub fn common_methods_rpc_resp(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "common_methods_rpc_resp");
+        let mut methods = vec![
+            bound::bytes_method(),
+            ImplItem::Verbatim(quote! { pub fn __str__(&self) -> String {self.pystr()} }),
+            ImplItem::Verbatim(quote! { pub fn __repr__(&self) -> String {self.pyrepr()} }),
+            bound::reduce_method(),
+            ImplItem::Verbatim(quote! {
+            /// Convert to a JSON string.
+            pub fn to_json(&self) -> String {self.py_to_json()} }),
+            ImplItem::Verbatim(quote! {
+            /// Build from a JSON string.
             ///
             /// Args:
-            ///     data (bytes): the serialized object.
+            ///     raw (str): The RPC JSON response (can be an error response).
             ///
-            /// Returns: the deserialized object.
+            /// Returns:
+            ///     Either the deserialized object or ``RPCError``.
             ///
             #[staticmethod]
-            pub fn from_bytes(data: &[u8]) -> PyResult<Self> {
-                Self::py_from_bytes(data)
-            }
-        }),
-        ImplItem::Verbatim(
-            quote! {pub fn __richcmp__(&self, other: &Self, op: pyo3::basic::CompareOp) -> pyo3::prelude::PyResult<bool> {self.richcmp(other, op)}},
-        ),
-    ];
-    ast.items.extend_from_slice(&methods);
-    TokenStream::from(ast.to_token_stream())
+            pub fn from_json(raw: &str) -> pyo3::prelude::PyResult<__solders_crate::rpc::responses::Resp<Self>> {Self::py_from_json(raw)} }),
+            ImplItem::Verbatim(quote! {
+                /// Deserialize from bytes.
+                ///
+                /// Args:
+                ///     data (bytes): the serialized object.
+                ///
+                /// Returns: the deserialized object.
+                ///
+                #[staticmethod]
+                pub fn from_bytes(data: &[u8]) -> pyo3::prelude::PyResult<Self> {
+                    Self::py_from_bytes(data)
+                }
+            }),
+            ImplItem::Verbatim(
+                quote! {pub fn __richcmp__(&self, other: &Self, op: pyo3::basic::CompareOp) -> pyo3::prelude::PyResult<bool> {self.richcmp(other, op)}},
+            ),
+        ];
+        if !defines_clone(&ast) {
+            methods.push(clone_method_item());
+        }
+        ast.items.extend_from_slice(&methods);
+        if let Err(e) = reject_ignored_getnewargs(&ast) {
+            return e.to_compile_error().into();
+        }
+        TokenStream::from(impl_output("common_methods_rpc_resp", &ast))
+    })
 }
 
 /// Add `__bytes__`, `__str__`, `__repr__`, `__reduce__`, `to_json`, `from_json`, `from_bytes` and `__richcmp__` using the `CommonMethodsRpcResp` trait.
+///
+/// Also add `clone` if not already defined.
 #[proc_macro_attribute]
-pub fn common_methods_rpc_resp_no_context(_: TokenStream, item: TokenStream) -> TokenStream {
-    let mut ast = parse_macro_input!(item as ItemImpl);
-    let methods = vec![
-        ImplItem::Verbatim(
-            quote! {pub fn __bytes__<'a>(&self, py: pyo3::prelude::Python<'a>) -> &'a pyo3::types::PyBytes  {self.pybytes(py)}},
-        ),
-        ImplItem::Verbatim(quote! { pub fn __str__(&self) -> String {self.pystr()} }),
-        ImplItem::Verbatim(quote! { pub fn __repr__(&self) -> String {self.pyrepr()} }),
-        ImplItem::Verbatim(
-            quote! { pub fn __reduce__(&self) -> pyo3::prelude::PyResult<(pyo3::prelude::PyObject, pyo3::prelude::PyObject)> {self.pyreduce()} },
-        ),
-        ImplItem::Verbatim(quote! {
-        /// Convert to a JSON string.
-        pub fn to_json(&self) -> String {self.py_to_json()} }),
-        ImplItem::Verbatim(quote! {
-        /// Build from a JSON string.
-        ///
-        /// Args:
-        ///     raw (str): The RPC JSON response (can be an error response).
-        ///
-        /// Returns:
-        ///     Either the deserialized object or ``RPCError``.
-        ///
-        #[staticmethod]
-        pub fn from_json(raw: &str) -> PyResult<Self> {Self::py_from_json(raw)} }),
-        ImplItem::Verbatim(quote! {
-            /// Deserialize from bytes.
+pub fn common_methods_rpc_resp_no_context(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "common_methods_rpc_resp_no_context");
+        let mut methods = vec![
+            bound::bytes_method(),
+            ImplItem::Verbatim(quote! { pub fn __str__(&self) -> String {self.pystr()} }),
+            ImplItem::Verbatim(quote! { pub fn __repr__(&self) -> String {self.pyrepr()} }),
+            bound::reduce_method(),
+            ImplItem::Verbatim(quote! {
+            /// Convert to a JSON string.
+            pub fn to_json(&self) -> String {self.py_to_json()} }),
+            ImplItem::Verbatim(quote! {
+            /// Build from a JSON string.
             ///
             /// Args:
-            ///     data (bytes): the serialized object.
+            ///     raw (str): The RPC JSON response (can be an error response).
             ///
-            /// Returns: the deserialized object.
+            /// Returns:
+            ///     Either the deserialized object or ``RPCError``.
             ///
             #[staticmethod]
-            pub fn from_bytes(data: &[u8]) -> PyResult<Self> {
-                Self::py_from_bytes(data)
-            }
-        }),
-        ImplItem::Verbatim(
-            quote! {pub fn __richcmp__(&self, other: &Self, op: pyo3::basic::CompareOp) -> pyo3::prelude::PyResult<bool> {self.richcmp(other, op)}},
-        ),
-    ];
-    ast.items.extend_from_slice(&methods);
-    TokenStream::from(ast.to_token_stream())
+            pub fn from_json(raw: &str) -> pyo3::prelude::PyResult<Self> {Self::py_from_json(raw)} }),
+            ImplItem::Verbatim(quote! {
+                /// Deserialize from bytes.
+                ///
+                /// Args:
+                ///     data (bytes): the serialized object.
+                ///
+                /// Returns: the deserialized object.
+                ///
+                #[staticmethod]
+                pub fn from_bytes(data: &[u8]) -> pyo3::prelude::PyResult<Self> {
+                    Self::py_from_bytes(data)
+                }
+            }),
+            ImplItem::Verbatim(
+                quote! {pub fn __richcmp__(&self, other: &Self, op: pyo3::basic::CompareOp) -> pyo3::prelude::PyResult<bool> {self.richcmp(other, op)}},
+            ),
+        ];
+        if !defines_clone(&ast) {
+            methods.push(clone_method_item());
+        }
+        ast.items.extend_from_slice(&methods);
+        if let Err(e) = reject_ignored_getnewargs(&ast) {
+            return e.to_compile_error().into();
+        }
+        TokenStream::from(impl_output("common_methods_rpc_resp_no_context", &ast))
+    })
 }
 
 /// Add an `id` getter to an RPC request object.
 ///
 /// By convention, assumes the `id` lives at `self.base.id`.
 #[proc_macro_attribute]
-pub fn rpc_id_getter(_: TokenStream, item: TokenStream) -> TokenStream {
-    let mut ast = parse_macro_input!(item as ItemImpl);
-    let to_add = quote! {
-    /// int: The ID of the RPC request.
-    #[getter]
-    pub fn id(&self) -> u64 {
-        self.base.id
-    }};
-    ast.items.push(ImplItem::Verbatim(to_add));
-    TokenStream::from(ast.to_token_stream())
+pub fn rpc_id_getter(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "rpc_id_getter");
+        let to_add = quote! {
+        /// int: The ID of the RPC request.
+        #[getter]
+        pub fn id(&self) -> u64 {
+            self.base.id
+        }};
+        ast.items.push(ImplItem::Verbatim(to_add));
+        TokenStream::from(impl_output("rpc_id_getter", &ast))
+    })
 }
 
 /// Add mappings to and from another enum that has the exact same fields.
@@ -267,30 +1281,3665 @@ pub fn rpc_id_getter(_: TokenStream, item: TokenStream) -> TokenStream {
 /// assert_eq!(Bar::from(b), Bar::B);
 ///
 #[proc_macro_attribute]
-pub fn enum_original_mapping(original: TokenStream, item: TokenStream) -> TokenStream {
-    let mut new_stream = proc_macro2::TokenStream::from(item.clone());
-    let ast = parse_macro_input!(item as ItemEnum);
-    let enum_name = ast.ident;
-    let orig = parse_macro_input!(original as Ident);
-    let variant_names: Vec<Ident> = ast.variants.into_iter().map(|v| v.ident).collect();
-    let from_impl = quote! {
-        impl From<#orig> for #enum_name {
-            fn from(left: #orig) -> Self {
-                match left {
-                    #(#orig::#variant_names => Self::#variant_names),*,
-                    _ => panic!("Unrecognized variant: {:?}", left)
+pub fn enum_original_mapping(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |original, item| {
+        let mut new_stream = proc_macro2::TokenStream::from(item.clone());
+        let ast = parse_target!(item as ItemEnum, "enum_original_mapping");
+        if original.is_empty() {
+            return errors::at(
+                ErrorKind::MissingArgument,
+                Span::call_site(),
+                "missing original enum path, e.g. `#[enum_original_mapping(OriginalEnum)]`",
+            )
+            .to_compile_error()
+            .into();
+        }
+        let orig = parse_macro_input!(original as syn::Path);
+        let enum_name = ast.ident.clone();
+        let variant_names = match fieldless_variants(&ast) {
+            Ok(names) if names.is_empty() => {
+                return errors::spanned(
+                    ErrorKind::WrongItem,
+                    &ast,
+                    "expected an enum with at least one variant",
+                )
+                .to_compile_error()
+                .into()
+            }
+            Ok(names) => names,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        let from_impl = quote! {
+            impl From<#orig> for #enum_name {
+                fn from(left: #orig) -> Self {
+                    match left {
+                        #(#orig::#variant_names => Self::#variant_names),*,
+                        _ => panic!("Unrecognized variant: {:?}", left)
+                    }
                 }
             }
-        }
 
-        impl From<#enum_name> for #orig {
-            fn from(left: #enum_name) -> Self {
-                match left {
-                    #(#enum_name::#variant_names => Self::#variant_names),*
+            impl From<#enum_name> for #orig {
+                fn from(left: #enum_name) -> Self {
+                    match left {
+                        #(#enum_name::#variant_names => Self::#variant_names),*
+                    }
                 }
             }
-        }
-    };
-    new_stream.extend(from_impl);
-    TokenStream::from(new_stream)
+        };
+        new_stream.extend(from_impl);
+        TokenStream::from(new_stream)
+    })
+}
+
+struct MappingArgs {
+    field: Member,
+    key: Type,
+    value: Type,
+}
+
+impl Parse for MappingArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            field: args.required_member("field")?,
+            key: args.required_ty("key")?,
+            value: args.required_ty("value")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Add the Python mapping protocol to a struct that wraps a map.
+///
+/// Adds `__getitem__` (raising `KeyError` for missing keys), `__contains__`, `__len__`,
+/// `__iter__` over the keys, `get(key, default=None)`, `keys`, `values` and `items`.
+/// Like a `dict`, `__contains__` and `get` accept any object, treating one that isn't a `key`
+/// as missing. The wrapped map must have `get`, `contains_key`, `len`, `keys` and `values`
+/// methods, like `HashMap` and `BTreeMap` do.
+///
+/// # Example
+///
+/// ```ignore
+/// #[pyclass]
+/// struct AccountMap(HashMap<Pubkey, Account>);
+///
+/// #[mapping_methods(field = 0, key = Pubkey, value = Account)]
+/// #[pymethods]
+/// impl AccountMap {}
+/// ```
+#[proc_macro_attribute]
+pub fn mapping_methods(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let MappingArgs { field, key, value } = parse_macro_input!(args as MappingArgs);
+        let mut ast = parse_target!(item as ItemImpl, "mapping_methods");
+        let any = bound::arg(quote! { PyAny });
+        let py_iterator = bound::owned(quote! { PyIterator });
+        let into_any = if cfg!(feature = "bound") {
+            quote! { into_bound }
+        } else {
+            quote! { into_ref }
+        };
+        let methods_in_scope = bound::methods_in_scope();
+        let methods = vec![
+            ImplItem::Verbatim(quote! {
+                pub fn __getitem__(
+                    &self,
+                    py: pyo3::prelude::Python<'_>,
+                    key: #key,
+                ) -> pyo3::prelude::PyResult<#value> {
+                    match self.#field.get(&key) {
+                        Some(value) => Ok(value.clone()),
+                        None => Err(pyo3::exceptions::PyKeyError::new_err(
+                            pyo3::IntoPy::<pyo3::prelude::PyObject>::into_py(key, py),
+                        )),
+                    }
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                pub fn __contains__(&self, key: #any) -> bool {
+                    #methods_in_scope
+                    key.extract::<#key>().is_ok_and(|key| self.#field.contains_key(&key))
+                }
+            }),
+            ImplItem::Verbatim(quote! { pub fn __len__(&self) -> usize {self.#field.len()} }),
+            ImplItem::Verbatim(quote! {
+                pub fn __iter__<'a>(&self, py: pyo3::prelude::Python<'a>) -> pyo3::prelude::PyResult<#py_iterator> {
+                    #methods_in_scope
+                    pyo3::IntoPy::<pyo3::prelude::PyObject>::into_py(self.keys(), py).#into_any(py).iter()
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+            /// Get the value for a key, or ``default`` if it's missing.
+            ///
+            /// Args:
+            ///     key: The key to look up.
+            ///     default: The value to return if ``key`` is missing.
+            ///
+            /// Returns:
+            ///     The value, or ``default``.
+            ///
+            #[pyo3(signature = (key, default = None))]
+            pub fn get(
+                &self,
+                py: pyo3::prelude::Python<'_>,
+                key: #any,
+                default: Option<pyo3::prelude::PyObject>,
+            ) -> pyo3::prelude::PyObject {
+                #methods_in_scope
+                match key.extract::<#key>().ok().and_then(|key| self.#field.get(&key)) {
+                    Some(value) => pyo3::IntoPy::into_py(value.clone(), py),
+                    None => default.unwrap_or_else(|| py.None()),
+                }
+            } }),
+            ImplItem::Verbatim(quote! {
+            /// Get the keys of the mapping.
+            ///
+            /// Returns:
+            ///     list: The keys.
+            ///
+            pub fn keys(&self) -> Vec<#key> {self.#field.keys().cloned().collect()} }),
+            ImplItem::Verbatim(quote! {
+            /// Get the values of the mapping.
+            ///
+            /// Returns:
+            ///     list: The values.
+            ///
+            pub fn values(&self) -> Vec<#value> {self.#field.values().cloned().collect()} }),
+            ImplItem::Verbatim(quote! {
+            /// Get the ``(key, value)`` pairs of the mapping.
+            ///
+            /// Returns:
+            ///     list[tuple]: The key-value pairs.
+            ///
+            pub fn items(&self) -> Vec<(#key, #value)> {
+                self.#field.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+            } }),
+        ];
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("mapping_methods", &ast))
+    })
+}
+
+struct IteratorArgs {
+    item: Type,
+    field: Member,
+}
+
+impl Parse for IteratorArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            item: args.required_ty("item")?,
+            field: args.required_member("field")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Add an `__iter__` that lazily iterates over a `Vec` field.
+///
+/// Also generates a hidden `<Name>Iterator` pyclass that holds a reference to the
+/// original object and yields one element at a time, so the field is never copied into a list.
+/// Elements are converted to `item` with `Into`.
+///
+/// # Example
+///
+/// ```ignore
+/// #[pyclass]
+/// struct Message { instructions: Vec<Instruction> }
+///
+/// #[pyiterator(item = Instruction, field = instructions)]
+/// #[pymethods]
+/// impl Message {}
+/// ```
+#[proc_macro_attribute]
+pub fn pyiterator(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let IteratorArgs {
+            item: item_type,
+            field,
+        } = parse_macro_input!(args as IteratorArgs);
+        let mut ast = parse_target!(item as ItemImpl, "pyiterator");
+        let name = match reject_generics(&ast, "pyiterator").and_then(|_| self_type_ident(&ast)) {
+            Ok(name) => name,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        let iter_name = format_ident!("{}Iterator", name);
+        ast.items.push(ImplItem::Verbatim(quote! {
+            pub fn __iter__(slf: pyo3::PyRef<'_, Self>) -> #iter_name {
+                #iter_name { owner: slf.into(), index: 0 }
+            }
+        }));
+        let iterator = quote! {
+            #[doc(hidden)]
+            #[pyo3::pyclass]
+            pub struct #iter_name {
+                owner: pyo3::Py<#name>,
+                index: usize,
+            }
+
+            #[pyo3::pymethods]
+            impl #iter_name {
+                fn __iter__(slf: pyo3::PyRef<'_, Self>) -> pyo3::PyRef<'_, Self> {
+                    slf
+                }
+
+                fn __next__(mut slf: pyo3::PyRefMut<'_, Self>) -> Option<#item_type> {
+                    let py = slf.py();
+                    let next = slf.owner.borrow(py).#field.get(slf.index).cloned().map(Into::into);
+                    slf.index += 1;
+                    next
+                }
+            }
+        };
+        let mut new_stream = impl_output("pyiterator", &ast);
+        new_stream.extend(iterator);
+        TokenStream::from(new_stream)
+    })
+}
+
+struct ConcatArgs {
+    field: Member,
+}
+
+impl Parse for ConcatArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            field: args.required_member("field")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Add `__add__` and `__iadd__` that concatenate two objects wrapping a `Vec` field.
+///
+/// `a + b` returns a copy of `a` whose field is extended with `b`'s elements, and `a += b`
+/// extends `a` in place, also when `b` is `a`. Any other operand raises `TypeError`. The class must be `Clone`,
+/// and so must the elements.
+///
+/// # Example
+///
+/// ```ignore
+/// #[pyclass]
+/// #[derive(Clone)]
+/// struct Instructions(Vec<Instruction>);
+///
+/// #[concat_dunders(field = 0)]
+/// #[pymethods]
+/// impl Instructions {}
+/// ```
+#[proc_macro_attribute]
+pub fn concat_dunders(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let ConcatArgs { field } = parse_macro_input!(args as ConcatArgs);
+        let mut ast = parse_target!(item as ItemImpl, "concat_dunders");
+        let name = match self_type_ident(&ast) {
+            Ok(name) => name.to_string(),
+            Err(e) => return e.to_compile_error().into(),
+        };
+        let add_message = format!("can only concatenate {} (not \"{{}}\") to {}", name, name);
+        let other = bound::arg(quote! { PyAny });
+        let (slf, cell) = if cfg!(feature = "bound") {
+            (quote! { &pyo3::Bound<'_, Self> }, quote! { Self })
+        } else {
+            (
+                quote! { &pyo3::PyCell<Self> },
+                quote! { pyo3::PyCell<Self> },
+            )
+        };
+        let methods_in_scope = bound::methods_in_scope();
+        // Only a failed downcast is a `TypeError`. Borrow errors are raised as they are.
+        let downcast = quote! {
+            other.downcast::<#cell>().map_err(|_| {
+                pyo3::exceptions::PyTypeError::new_err(format!(
+                    #add_message,
+                    other.get_type().name().map(|n| n.to_string()).unwrap_or_default()
+                ))
+            })?
+        };
+        let methods = vec![
+            ImplItem::Verbatim(quote! {
+                pub fn __add__(&self, other: #other) -> pyo3::prelude::PyResult<Self> {
+                    #methods_in_scope
+                    let other = #downcast.try_borrow()?;
+                    let mut new = self.clone();
+                    new.#field.extend(other.#field.iter().cloned());
+                    Ok(new)
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                pub fn __iadd__(slf: #slf, other: #other) -> pyo3::prelude::PyResult<()> {
+                    #methods_in_scope
+                    // `x += x` would otherwise borrow `x` mutably and immutably at once.
+                    if slf.is(other) {
+                        let mut this = slf.try_borrow_mut()?;
+                        let copy = this.#field.clone();
+                        this.#field.extend(copy);
+                        return Ok(());
+                    }
+                    let other = #downcast.try_borrow()?;
+                    slf.try_borrow_mut()?.#field.extend(other.#field.iter().cloned());
+                    Ok(())
+                }
+            }),
+        ];
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("concat_dunders", &ast))
+    })
+}
+
+struct BufferArgs {
+    field: Option<Member>,
+}
+
+impl Parse for BufferArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            field: args.member("field")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Add `__getbuffer__` and `__releasebuffer__`, exposing the object's bytes as a read-only buffer.
+///
+/// This lets `memoryview`, NumPy and `hashlib` read the bytes without copying.
+/// The bytes come from `AsRef<[u8]>` on `Self`, or on the given field with
+/// `#[py_buffer(field = 0)]`. The object must not change while a buffer is held,
+/// so only use this on immutable types.
+#[proc_macro_attribute]
+pub fn py_buffer(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let BufferArgs { field } = parse_macro_input!(args as BufferArgs);
+        let mut ast = parse_target!(item as ItemImpl, "py_buffer");
+        let bytes = match field {
+            Some(field) => quote! { AsRef::<[u8]>::as_ref(&slf.#field) },
+            None => quote! { AsRef::<[u8]>::as_ref(&*slf) },
+        };
+        let methods = vec![
+            ImplItem::Verbatim(quote! {
+                pub unsafe fn __getbuffer__(
+                    slf: pyo3::PyRef<'_, Self>,
+                    view: *mut pyo3::ffi::Py_buffer,
+                    flags: std::os::raw::c_int,
+                ) -> pyo3::prelude::PyResult<()> {
+                    use pyo3::AsPyPointer;
+                    if view.is_null() {
+                        return Err(pyo3::exceptions::PyBufferError::new_err("View is null"));
+                    }
+                    if (flags & pyo3::ffi::PyBUF_WRITABLE) == pyo3::ffi::PyBUF_WRITABLE {
+                        return Err(pyo3::exceptions::PyBufferError::new_err("Object is not writable"));
+                    }
+                    pyo3::ffi::Py_INCREF(slf.as_ptr());
+                    (*view).obj = slf.as_ptr();
+                    let bytes: &[u8] = #bytes;
+                    (*view).buf = bytes.as_ptr() as *mut std::os::raw::c_void;
+                    (*view).len = bytes.len() as isize;
+                    (*view).readonly = 1;
+                    (*view).itemsize = 1;
+                    (*view).format = if (flags & pyo3::ffi::PyBUF_FORMAT) == pyo3::ffi::PyBUF_FORMAT {
+                        b"B\0".as_ptr() as *mut std::os::raw::c_char
+                    } else {
+                        std::ptr::null_mut()
+                    };
+                    (*view).ndim = 1;
+                    (*view).shape = if (flags & pyo3::ffi::PyBUF_ND) == pyo3::ffi::PyBUF_ND {
+                        &mut (*view).len
+                    } else {
+                        std::ptr::null_mut()
+                    };
+                    (*view).strides = if (flags & pyo3::ffi::PyBUF_STRIDES) == pyo3::ffi::PyBUF_STRIDES {
+                        &mut (*view).itemsize
+                    } else {
+                        std::ptr::null_mut()
+                    };
+                    (*view).suboffsets = std::ptr::null_mut();
+                    (*view).internal = std::ptr::null_mut();
+                    Ok(())
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                pub unsafe fn __releasebuffer__(&self, _view: *mut pyo3::ffi::Py_buffer) {}
+            }),
+        ];
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("py_buffer", &ast))
+    })
+}
+
+/// Add an `__array_interface__` property describing the object's bytes as a 1-D `uint8` array.
+///
+/// This lets `np.asarray(obj)` view the bytes directly instead of building the array element by element.
+/// NumPy keeps a reference to the object, so the bytes stay valid for the array's lifetime.
+/// Like `py_buffer`, the bytes come from `AsRef<[u8]>` on `Self` or on the field given with
+/// `#[array_interface(field = 0)]`, and the type must be immutable.
+#[proc_macro_attribute]
+pub fn array_interface(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let BufferArgs { field } = parse_macro_input!(args as BufferArgs);
+        let mut ast = parse_target!(item as ItemImpl, "array_interface");
+        let bytes = match field {
+            Some(field) => quote! { AsRef::<[u8]>::as_ref(&self.#field) },
+            None => quote! { AsRef::<[u8]>::as_ref(self) },
+        };
+        let py_dict = bound::owned(quote! { PyDict });
+        let new_dict = bound::new(quote! { PyDict });
+        let methods_in_scope = bound::methods_in_scope();
+        ast.items.push(ImplItem::Verbatim(quote! {
+            #[getter]
+            pub fn __array_interface__<'a>(&self, py: pyo3::prelude::Python<'a>) -> pyo3::prelude::PyResult<#py_dict> {
+                #methods_in_scope
+                let bytes: &[u8] = #bytes;
+                let interface = #new_dict(py);
+                interface.set_item("shape", (bytes.len(),))?;
+                interface.set_item("typestr", "|u1")?;
+                interface.set_item("data", (bytes.as_ptr() as usize, true))?;
+                interface.set_item("version", 3)?;
+                Ok(interface)
+            }
+        }));
+        TokenStream::from(impl_output("array_interface", &ast))
+    })
+}
+
+/// The single field of a newtype struct.
+fn newtype_field(ast: &syn::DeriveInput) -> syn::Result<&syn::Field> {
+    match &ast.data {
+        syn::Data::Struct(data) if data.fields.len() == 1 => Ok(data.fields.iter().next().unwrap()),
+        _ => Err(errors::spanned(
+            ErrorKind::WrongItem,
+            &ast.ident,
+            "expected a struct with exactly one field",
+        )),
+    }
+}
+
+/// Derive `FromPyObject` for a newtype over bytes, accepting any bytes-like Python input.
+///
+/// The generated impl accepts `bytes`, `bytearray`, `memoryview` (or anything else exposing
+/// the buffer protocol) and sequences of ints. An int outside `range(0, 256)` is reported with
+/// its index as a `ValueError`. If the wrapped type is a `[u8; N]` array,
+/// the length is checked with a clear `ValueError`; otherwise the wrapped type is built
+/// with `TryFrom<&[u8]>` and its error message is passed through.
+///
+/// This is meant for argument types used by constructors and `from_bytes`. Don't derive it on
+/// `#[pyclass]` types, since pyo3 already implements `FromPyObject` for those.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(FromBytesLike)]
+/// pub struct PubkeyBytes([u8; 32]);
+///
+/// #[new]
+/// pub fn new(pubkey_bytes: PubkeyBytes) -> Self { Self::from(pubkey_bytes.0) }
+/// ```
+#[proc_macro_derive(FromBytesLike)]
+pub fn from_bytes_like(item: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(item as syn::DeriveInput);
+    let name = &ast.ident;
+    let field = match newtype_field(&ast) {
+        Ok(field) => field,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let construct = match &field.ident {
+        Some(field_name) => quote! { Self { #field_name: inner } },
+        None => quote! { Self(inner) },
+    };
+    let inner_type = &field.ty;
+    let convert = match inner_type {
+        Type::Array(arr) => {
+            let len = &arr.len;
+            quote! {
+                let inner = <#inner_type>::try_from(bytes.as_slice()).map_err(|_| {
+                    pyo3::exceptions::PyValueError::new_err(format!("expected {} bytes, got {}", #len, bytes.len()))
+                })?;
+            }
+        }
+        _ => quote! {
+            let inner = <#inner_type>::try_from(bytes.as_slice())
+                .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+        },
+    };
+    let mut generics = ast.generics.clone();
+    generics.params.insert(0, syn::parse_quote!('source));
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let extract = bound::extract_signature();
+    let methods_in_scope = bound::methods_in_scope();
+    let buffer = bound::buffer(quote! { ob });
+    let item_type_name = if cfg!(feature = "bound") {
+        quote! { item.bind(py).get_type().name()? }
+    } else {
+        quote! { item.as_ref(py).get_type().name()? }
+    };
+    let expanded = quote! {
+        impl #impl_generics pyo3::FromPyObject<'source> for #name #ty_generics #where_clause {
+            #extract {
+                #methods_in_scope
+                let bytes: Vec<u8> = if let Ok(b) = ob.downcast::<pyo3::types::PyBytes>() {
+                    b.as_bytes().to_vec()
+                } else if let Ok(b) = ob.downcast::<pyo3::types::PyByteArray>() {
+                    b.to_vec()
+                } else if let Ok(buffer) = #buffer {
+                    buffer.to_vec(ob.py())?
+                } else if let Ok(items) = ob.extract::<Vec<pyo3::prelude::PyObject>>() {
+                    let py = ob.py();
+                    let mut ints = Vec::with_capacity(items.len());
+                    for (index, item) in items.iter().enumerate() {
+                        match item.extract::<u8>(py) {
+                            Ok(int) => ints.push(int),
+                            // Ints that don't fit in a `u8` raise `OverflowError`.
+                            Err(e) if e.is_instance_of::<pyo3::exceptions::PyOverflowError>(py) => {
+                                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                                    "byte at index {} must be in range(0, 256), got {}",
+                                    index, item
+                                )));
+                            }
+                            Err(_) => {
+                                return Err(pyo3::exceptions::PyTypeError::new_err(format!(
+                                    "expected a sequence of ints, got {} at index {}",
+                                    #item_type_name, index
+                                )));
+                            }
+                        }
+                    }
+                    ints
+                } else {
+                    return Err(pyo3::exceptions::PyTypeError::new_err(format!(
+                        "expected bytes, bytearray, memoryview or a sequence of ints, got {}",
+                        ob.get_type().name()?
+                    )));
+                };
+                #convert
+                Ok(#construct)
+            }
+        }
+    };
+    TokenStream::from(expanded)
+}
+
+/// A `proptest` strategy producing values of a fixed-size array type.
+///
+/// Goes through a `Vec` rather than `any::<[T; N]>()` because proptest only implements
+/// `Arbitrary` for arrays up to 32 elements, and signatures are 64 bytes.
+fn array_strategy(arr: &syn::TypeArray) -> proc_macro2::TokenStream {
+    let elem = &arr.elem;
+    let len = &arr.len;
+    quote! {
+        proptest::collection::vec(proptest::arbitrary::any::<#elem>(), #len).prop_map(|v| {
+            match <[#elem; #len]>::try_from(v) {
+                Ok(arr) => arr,
+                Err(_) => unreachable!("the strategy yields exactly {} elements", #len),
+            }
+        })
+    }
+}
+
+/// Derive `proptest::arbitrary::Arbitrary` for a newtype wrapper.
+///
+/// Named `SoldersArbitrary` so it doesn't clash with the `Arbitrary` derives exported by
+/// `proptest-derive` and the `arbitrary` crate.
+///
+/// If the wrapped field is a byte array (or any fixed-size array), values are drawn from a
+/// strategy over that array. Otherwise the derive delegates to the inner type's own
+/// `Arbitrary` impl. For wrappers around foreign types that can't implement `Arbitrary`
+/// but can be built from bytes, annotate the field with `#[solders_arbitrary(from_bytes = N)]`
+/// to draw `[u8; N]` and convert it with the inner type's `From<[u8; N]>`.
+///
+/// The calling crate needs `proptest` as a (dev-)dependency.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(SoldersArbitrary)]
+/// pub struct Signature(#[solders_arbitrary(from_bytes = 64)] SignatureOriginal);
+///
+/// proptest! {
+///     #[test]
+///     fn bytes_round_trip(sig: Signature) {
+///         prop_assert_eq!(Signature::from_bytes(&sig.__bytes__()).unwrap(), sig);
+///     }
+/// }
+/// ```
+#[proc_macro_derive(SoldersArbitrary, attributes(solders_arbitrary))]
+pub fn solders_arbitrary(item: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(item as syn::DeriveInput);
+    match arbitrary_impl(&ast) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn arbitrary_impl(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+    let field = newtype_field(ast)?;
+    let construct = match &field.ident {
+        Some(field_name) => quote! { #name { #field_name: inner } },
+        None => quote! { #name(inner) },
+    };
+    let inner_type = &field.ty;
+    let from_bytes = match field
+        .attrs
+        .iter()
+        .find(|a| a.path.is_ident("solders_arbitrary"))
+    {
+        Some(attr) => {
+            let mut args: MacroArgs = attr.parse_args()?;
+            let len = args.required_lit_int("from_bytes")?;
+            args.finish()?;
+            Some(len)
+        }
+        None => None,
+    };
+    let strategy = match (from_bytes, inner_type) {
+        (Some(len), _) => {
+            let bytes = array_strategy(&syn::parse_quote!([u8; #len]));
+            quote! { #bytes.prop_map(<#inner_type as From<[u8; #len]>>::from) }
+        }
+        (None, Type::Array(arr)) => array_strategy(arr),
+        (None, _) => quote! { proptest::arbitrary::any::<#inner_type>() },
+    };
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics proptest::arbitrary::Arbitrary for #name #ty_generics #where_clause {
+            type Parameters = ();
+            type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+            fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                use proptest::strategy::Strategy;
+                #strategy.prop_map(|inner| #construct).boxed()
+            }
+        }
+    })
+}
+
+/// Derive `PartialEq` between a newtype wrapper and the type it wraps, in both directions.
+///
+/// The wrapper compares equal to a value of the wrapped type when its field does, which is
+/// the same value its `AsRef` and `From` impls expose. So `wrapper == original` and
+/// `original == wrapper` work without converting either side.
+///
+/// # Example
+///
+/// ```rust
+/// use solders_macros::WrapperEq;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct PubkeyOriginal([u8; 32]);
+///
+/// #[derive(WrapperEq)]
+/// struct Pubkey(PubkeyOriginal);
+///
+/// assert!(Pubkey(PubkeyOriginal([1; 32])) == PubkeyOriginal([1; 32]));
+/// assert!(PubkeyOriginal([1; 32]) != Pubkey(PubkeyOriginal([2; 32])));
+/// ```
+#[proc_macro_derive(WrapperEq)]
+pub fn wrapper_eq(item: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(item as syn::DeriveInput);
+    match wrapper_eq_impl(&ast) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn wrapper_eq_impl(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+    let field = newtype_field(ast)?;
+    let member = match &field.ident {
+        Some(field_name) => Member::Named(field_name.clone()),
+        None => Member::Unnamed(syn::Index::from(0)),
+    };
+    let inner_type = &field.ty;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics PartialEq<#inner_type> for #name #ty_generics #where_clause {
+            fn eq(&self, other: &#inner_type) -> bool {
+                self.#member == *other
+            }
+        }
+
+        impl #impl_generics PartialEq<#name #ty_generics> for #inner_type #where_clause {
+            fn eq(&self, other: &#name #ty_generics) -> bool {
+                *self == other.#member
+            }
+        }
+    })
+}
+
+struct BoolArgs {
+    falsy_if: Option<Ident>,
+}
+
+impl Parse for BoolArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            falsy_if: args.ident("falsy_if")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Add a `__bool__` so the object can be falsy in Python.
+///
+/// By default the object is falsy when it equals `Self::default()`.
+/// Pass a predicate method to use instead, e.g. `#[bool_dunder(falsy_if = is_empty)]`
+/// makes `__bool__` return `!self.is_empty()`.
+///
+/// # Example
+///
+#[cfg_attr(not(feature = "multiple-pymethods"), doc = "```rust")]
+#[cfg_attr(feature = "multiple-pymethods", doc = "```ignore")]
+/// use solders_macros::bool_dunder;
+///
+/// #[derive(Debug, Default, PartialEq)]
+/// struct Foo(u8);
+///
+/// #[bool_dunder]
+/// impl Foo {}
+///
+/// #[derive(Debug)]
+/// struct Bar(Vec<u8>);
+///
+/// #[bool_dunder(falsy_if = is_empty)]
+/// impl Bar {
+///   pub fn is_empty(&self) -> bool {
+///      self.0.is_empty()
+///   }
+/// }
+///
+/// assert!(!Foo(0).__bool__());
+/// assert!(Foo(3).__bool__());
+/// assert!(!Bar(vec![]).__bool__());
+/// ```
+#[proc_macro_attribute]
+pub fn bool_dunder(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let BoolArgs { falsy_if } = parse_macro_input!(args as BoolArgs);
+        let mut ast = parse_target!(item as ItemImpl, "bool_dunder");
+        let body = match falsy_if {
+            Some(predicate) => quote! { !self.#predicate() },
+            None => quote! { *self != Self::default() },
+        };
+        ast.items.push(ImplItem::Verbatim(
+            quote! { pub fn __bool__(&self) -> bool {#body} },
+        ));
+        TokenStream::from(impl_output("bool_dunder", &ast))
+    })
+}
+
+struct LenArgs {
+    method: Option<Ident>,
+    field: Option<Member>,
+}
+
+impl Parse for LenArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            method: args.ident("method")?,
+            field: args.member("field")?,
+        };
+        args.finish()?;
+        if parsed.method.is_some() && parsed.field.is_some() {
+            return Err(errors::at(
+                ErrorKind::InvalidArgument,
+                Span::call_site(),
+                "`method` and `field` are mutually exclusive",
+            ));
+        }
+        Ok(parsed)
+    }
+}
+
+/// Add a `__len__`.
+///
+/// Delegates to `self.len()` by default. Use `#[len_dunder(method = num_items)]` to call another method,
+/// or `#[len_dunder(field = instructions)]` to use `self.instructions.len()`.
+///
+/// # Example
+///
+#[cfg_attr(not(feature = "multiple-pymethods"), doc = "```rust")]
+#[cfg_attr(feature = "multiple-pymethods", doc = "```ignore")]
+/// use solders_macros::len_dunder;
+///
+/// struct Foo {
+///   items: Vec<u8>,
+/// }
+///
+/// #[len_dunder(field = items)]
+/// impl Foo {}
+///
+/// let foo = Foo { items: vec![1, 2, 3] };
+/// assert_eq!(3, foo.__len__());
+/// ```
+#[proc_macro_attribute]
+pub fn len_dunder(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let LenArgs { method, field } = parse_macro_input!(args as LenArgs);
+        let mut ast = parse_target!(item as ItemImpl, "len_dunder");
+        let body = match (method, field) {
+            (Some(method), _) => quote! { self.#method() },
+            (None, Some(field)) => quote! { self.#field.len() },
+            (None, None) => quote! { self.len() },
+        };
+        ast.items.push(ImplItem::Verbatim(quote! {
+            /// Return len(self).
+            pub fn __len__(&self) -> usize {#body}
+        }));
+        TokenStream::from(impl_output("len_dunder", &ast))
+    })
+}
+
+struct SizeofArgs {
+    heap: Vec<Member>,
+}
+
+impl Parse for SizeofArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            heap: args.member_list("heap")?.unwrap_or_default(),
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Add a `__sizeof__` that includes the Rust-side memory of the object.
+///
+/// Returns `object.__sizeof__(self)`, which for a pyclass is the size of the Python object
+/// with the Rust struct inside it. With `#[sizeof_dunder(heap(instructions, 0))]` the heap
+/// allocation of each listed `Vec` field (its capacity times the element size) is added as
+/// well. Heap memory owned by the elements themselves isn't counted.
+///
+/// # Example
+///
+/// ```ignore
+/// #[pyclass]
+/// struct Message {
+///     instructions: Vec<Instruction>,
+/// }
+///
+/// #[sizeof_dunder(heap(instructions))]
+/// #[pymethods]
+/// impl Message {}
+/// ```
+#[proc_macro_attribute]
+pub fn sizeof_dunder(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let SizeofArgs { heap } = parse_macro_input!(args as SizeofArgs);
+        let mut ast = parse_target!(item as ItemImpl, "sizeof_dunder");
+        let slf = if cfg!(feature = "bound") {
+            quote! { &pyo3::Bound<'_, Self> }
+        } else {
+            quote! { &pyo3::PyCell<Self> }
+        };
+        let object = bound::get_type(quote! { slf.py() }, quote! { pyo3::types::PyAny });
+        let methods_in_scope = bound::methods_in_scope();
+        ast.items.push(ImplItem::Verbatim(quote! {
+            /// Return the size of the object in memory, in bytes.
+            pub fn __sizeof__(slf: #slf) -> pyo3::prelude::PyResult<usize> {
+                #methods_in_scope
+                fn vec_heap<T>(v: &Vec<T>) -> usize {
+                    v.capacity() * std::mem::size_of::<T>()
+                }
+                let base: usize = #object.call_method1("__sizeof__", (slf,))?.extract()?;
+                let this = slf.try_borrow()?;
+                Ok(base #(+ vec_heap(&this.#heap))*)
+            }
+        }));
+        TokenStream::from(impl_output("sizeof_dunder", &ast))
+    })
+}
+
+/// The variant names of a fieldless enum.
+fn fieldless_variants(ast: &ItemEnum) -> syn::Result<Vec<Ident>> {
+    ast.variants
+        .iter()
+        .map(|v| match v.fields {
+            syn::Fields::Unit => Ok(v.ident.clone()),
+            _ => Err(errors::spanned(
+                ErrorKind::WrongItem,
+                v,
+                "expected a fieldless enum",
+            )),
+        })
+        .collect()
+}
+
+fn int_dunder_methods(enum_name: &Ident, variant_names: &[Ident]) -> proc_macro2::TokenStream {
+    quote! {
+        pub fn __int__(&self) -> u8 {
+            match self {
+                #(Self::#variant_names => Self::#variant_names as u8),*
+            }
+        }
+
+        pub fn __index__(&self) -> u8 {
+            self.__int__()
+        }
+
+        /// Build from an integer.
+        ///
+        /// Args:
+        ///     value (int): The integer value of the variant.
+        ///
+        /// Returns:
+        ///     The matching variant.
+        ///
+        /// Raises:
+        ///     ValueError: If no variant has this value.
+        ///
+        #[staticmethod]
+        pub fn from_int(value: i64) -> pyo3::prelude::PyResult<Self> {
+            #(if value == i64::from(Self::#variant_names as u8) {
+                return Ok(Self::#variant_names);
+            })*
+            Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "{} is not a valid {}",
+                value,
+                stringify!(#enum_name)
+            )))
+        }
+    }
+}
+
+/// Add `__int__`, `__index__` and a `from_int` staticmethod to a fieldless enum.
+///
+/// The integer value is the variant's discriminant as a `u8`.
+/// `from_int` raises `ValueError` for values that don't match a variant, including negative
+/// and out-of-range ones.
+///
+/// Apply this to the enum definition. The methods go in a new `#[pymethods]` block, so
+/// if the enum has other `#[pymethods]` blocks you need pyo3's `multiple-pymethods` feature.
+#[proc_macro_attribute]
+pub fn int_dunders(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut new_stream = proc_macro2::TokenStream::from(item.clone());
+        let ast = parse_target!(item as ItemEnum, "int_dunders");
+        let enum_name = &ast.ident;
+        let variant_names = match fieldless_variants(&ast) {
+            Ok(v) => v,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        let methods = int_dunder_methods(enum_name, &variant_names);
+        let methods = quote! {
+            #[pyo3::pymethods]
+            impl #enum_name {
+                #methods
+            }
+        };
+        new_stream.extend(methods);
+        TokenStream::from(new_stream)
+    })
+}
+
+/// Make a fieldless enum behave like Python's `enum.IntEnum`.
+///
+/// Adds everything from `int_dunders`, plus a `__richcmp__` that compares numerically against
+/// both the enum and plain ints, and a `__hash__` that matches the hash of the integer value.
+/// Comparisons with anything else return `NotImplemented`.
+///
+/// Like `int_dunders`, apply this to the enum definition.
+#[proc_macro_attribute]
+pub fn int_enum(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut new_stream = proc_macro2::TokenStream::from(item.clone());
+        let ast = parse_target!(item as ItemEnum, "int_enum");
+        let enum_name = &ast.ident;
+        let variant_names = match fieldless_variants(&ast) {
+            Ok(v) => v,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        let int_methods = int_dunder_methods(enum_name, &variant_names);
+        let other = bound::arg(quote! { PyAny });
+        let methods_in_scope = bound::methods_in_scope();
+        let methods = quote! {
+            #[pyo3::pymethods]
+            impl #enum_name {
+                #int_methods
+
+                pub fn __hash__(&self) -> u64 {
+                    self.__int__().into()
+                }
+
+                pub fn __richcmp__(&self, other: #other, op: pyo3::basic::CompareOp) -> pyo3::prelude::PyObject {
+                    #methods_in_scope
+                    use pyo3::prelude::IntoPy;
+                    let py = other.py();
+                    let other_value = if let Ok(other_enum) = other.extract::<pyo3::PyRef<Self>>() {
+                        i64::from(other_enum.__int__())
+                    } else if let Ok(other_int) = other.extract::<i64>() {
+                        other_int
+                    } else {
+                        return py.NotImplemented();
+                    };
+                    let value = i64::from(self.__int__());
+                    let result = match op {
+                        pyo3::basic::CompareOp::Lt => value < other_value,
+                        pyo3::basic::CompareOp::Le => value <= other_value,
+                        pyo3::basic::CompareOp::Eq => value == other_value,
+                        pyo3::basic::CompareOp::Ne => value != other_value,
+                        pyo3::basic::CompareOp::Gt => value > other_value,
+                        pyo3::basic::CompareOp::Ge => value >= other_value,
+                    };
+                    result.into_py(py)
+                }
+            }
+        };
+        new_stream.extend(methods);
+        TokenStream::from(new_stream)
+    })
+}
+
+struct EnumStrArgs {
+    rename_all: RenameRule,
+}
+
+impl Parse for EnumStrArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let rename_all = match args.lit_str("rename_all")? {
+            Some(lit) => RenameRule::from_lit(&lit)?,
+            None => RenameRule::None,
+        };
+        args.finish()?;
+        Ok(Self { rename_all })
+    }
+}
+
+/// Add `__str__` and a `from_string` staticmethod to a fieldless enum.
+///
+/// The string form of each variant is its name, renamed with the same rules as serde's
+/// `rename_all`, e.g. `#[enum_str_mapping(rename_all = "camelCase")]`.
+/// `from_string` raises `ValueError` for unrecognized strings.
+///
+/// Apply this to the enum definition. The methods go in a new `#[pymethods]` block, so
+/// if the enum has other `#[pymethods]` blocks you need pyo3's `multiple-pymethods` feature.
+#[proc_macro_attribute]
+pub fn enum_str_mapping(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let EnumStrArgs { rename_all } = parse_macro_input!(args as EnumStrArgs);
+        let mut new_stream = proc_macro2::TokenStream::from(item.clone());
+        let ast = parse_target!(item as ItemEnum, "enum_str_mapping");
+        let enum_name = &ast.ident;
+        let variant_names = match fieldless_variants(&ast) {
+            Ok(v) => v,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        let wire_names: Vec<String> = variant_names
+            .iter()
+            .map(|v| rename_all.apply(&v.to_string()))
+            .collect();
+        let methods = quote! {
+            #[pyo3::pymethods]
+            impl #enum_name {
+                pub fn __str__(&self) -> &'static str {
+                    match self {
+                        #(Self::#variant_names => #wire_names),*
+                    }
+                }
+
+                /// Build from a string.
+                ///
+                /// Args:
+                ///     raw (str): The string form of the variant.
+                ///
+                /// Returns:
+                ///     The matching variant.
+                ///
+                /// Raises:
+                ///     ValueError: If the string doesn't match a variant.
+                ///
+                #[staticmethod]
+                pub fn from_string(raw: &str) -> pyo3::prelude::PyResult<Self> {
+                    match raw {
+                        #(#wire_names => Ok(Self::#variant_names),)*
+                        _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                            "unrecognized {}: {}",
+                            stringify!(#enum_name),
+                            raw
+                        ))),
+                    }
+                }
+            }
+        };
+        new_stream.extend(methods);
+        TokenStream::from(new_stream)
+    })
+}
+
+/// Add an `is_<variant>()` method for every variant of an enum, with the variant name in snake case.
+///
+/// For example, a variant `InsufficientFunds` gets `is_insufficient_funds()`.
+///
+/// Apply this to the enum definition. The methods go in a new `#[pymethods]` block, so
+/// if the enum has other `#[pymethods]` blocks you need pyo3's `multiple-pymethods` feature.
+#[proc_macro_attribute]
+pub fn enum_predicates(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut new_stream = proc_macro2::TokenStream::from(item.clone());
+        let ast = parse_target!(item as ItemEnum, "enum_predicates");
+        let enum_name = &ast.ident;
+        let variant_names: Vec<&Ident> = ast.variants.iter().map(|v| &v.ident).collect();
+        let predicate_names = variant_names.iter().map(|v| {
+            format_ident!(
+                "is_{}",
+                RenameRule::SnakeCase.apply(&v.to_string()),
+                span = v.span()
+            )
+        });
+        let docs = variant_names
+            .iter()
+            .map(|v| format!(" bool: Whether this is ``{}``.", v));
+        let methods = quote! {
+            #[pyo3::pymethods]
+            impl #enum_name {
+                #(
+                    #[doc = #docs]
+                    pub fn #predicate_names(&self) -> bool {
+                        matches!(self, Self::#variant_names { .. })
+                    }
+                )*
+            }
+        };
+        new_stream.extend(methods);
+        TokenStream::from(new_stream)
+    })
+}
+
+/// The variant names of an enum whose variants each wrap exactly one unnamed field.
+fn newtype_variants(data: &syn::DataEnum) -> syn::Result<Vec<Ident>> {
+    data.variants
+        .iter()
+        .map(|v| match &v.fields {
+            syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Ok(v.ident.clone()),
+            _ => Err(errors::spanned(
+                ErrorKind::WrongItem,
+                v,
+                "expected every variant to wrap exactly one unnamed field",
+            )),
+        })
+        .collect()
+}
+
+/// Derive `IntoPy<PyObject>` for an enum by converting whichever variant's payload is present.
+///
+/// Each variant must wrap exactly one value that implements `IntoPy<PyObject>`,
+/// so functions can return the enum as a Python union type.
+///
+/// With the `bound` feature it also derives `ToPyObject`, which the `Bound` API takes in
+/// places like `PyList::new_bound`, by converting a clone, so the enum must also be `Clone`.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(EnumIntoPy)]
+/// pub enum UiInstruction {
+///     Parsed(UiParsedInstruction),
+///     Compiled(UiCompiledInstruction),
+/// }
+/// ```
+#[proc_macro_derive(EnumIntoPy)]
+pub fn enum_into_py(item: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(item as syn::DeriveInput);
+    match union_into_py(&ast) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn union_into_py(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+    let variant_names = match &ast.data {
+        syn::Data::Enum(data) => newtype_variants(data)?,
+        _ => {
+            return Err(errors::spanned(
+                ErrorKind::WrongItem,
+                &ast.ident,
+                "expected an enum",
+            ))
+        }
+    };
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let mut expanded = quote! {
+        impl #impl_generics pyo3::IntoPy<pyo3::prelude::PyObject> for #name #ty_generics #where_clause {
+            fn into_py(self, py: pyo3::prelude::Python<'_>) -> pyo3::prelude::PyObject {
+                match self {
+                    #(Self::#variant_names(x) => pyo3::IntoPy::into_py(x, py)),*
+                }
+            }
+        }
+    };
+    if cfg!(feature = "bound") {
+        expanded.extend(quote! {
+            impl #impl_generics pyo3::ToPyObject for #name #ty_generics #where_clause {
+                fn to_object(&self, py: pyo3::prelude::Python<'_>) -> pyo3::prelude::PyObject {
+                    pyo3::IntoPy::into_py(::std::clone::Clone::clone(self), py)
+                }
+            }
+        });
+    }
+    Ok(expanded)
+}
+
+/// Derive `FromPyObject` for an enum by trying to extract each variant's payload in order.
+///
+/// This is the input-side counterpart of `EnumIntoPy`: each variant must wrap exactly one value
+/// that implements `FromPyObject`, and the first variant that extracts successfully wins.
+/// If none do, the `TypeError` lists why each variant failed.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(EnumFromPy)]
+/// pub enum Signer {
+///     Keypair(Keypair),
+///     Presigner(Presigner),
+///     NullSigner(NullSigner),
+/// }
+/// ```
+#[proc_macro_derive(EnumFromPy)]
+pub fn enum_from_py(item: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(item as syn::DeriveInput);
+    match union_from_py(&ast) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn union_from_py(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+    let variant_names = match &ast.data {
+        syn::Data::Enum(data) => newtype_variants(data)?,
+        _ => {
+            return Err(errors::spanned(
+                ErrorKind::WrongItem,
+                &ast.ident,
+                "expected an enum",
+            ))
+        }
+    };
+    let mut generics = ast.generics.clone();
+    generics.params.insert(0, syn::parse_quote!('source));
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let extract = bound::extract_signature();
+    let methods_in_scope = bound::methods_in_scope();
+    Ok(quote! {
+        impl #impl_generics pyo3::FromPyObject<'source> for #name #ty_generics #where_clause {
+            #extract {
+                #methods_in_scope
+                let mut errors: Vec<String> = Vec::new();
+                #(
+                    match ob.extract() {
+                        Ok(x) => return Ok(Self::#variant_names(x)),
+                        Err(e) => errors.push(format!("{}: {}", stringify!(#variant_names), e)),
+                    }
+                )*
+                Err(pyo3::exceptions::PyTypeError::new_err(format!(
+                    "failed to extract {} from {}: {}",
+                    stringify!(#name),
+                    ob.get_type().name()?,
+                    errors.join("; ")
+                )))
+            }
+        }
+    })
+}
+
+struct ComplexEnumArgs {
+    module: Option<syn::LitStr>,
+}
+
+impl Parse for ComplexEnumArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            module: args.lit_str("module")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Expose a data-carrying enum to Python as one class per variant.
+///
+/// For an enum `Foo`, this generates:
+///
+/// - A `#[pyclass]` named `Foo<Variant>` for every variant, with a `#[new]` constructor and
+///   a getter per field. Tuple variant fields are named `field_0`, `field_1` and so on.
+///   Variant and field doc comments are carried over.
+/// - A `FooUnion` enum wrapping those classes, with `IntoPy` and `FromPyObject` impls
+///   as generated by `EnumIntoPy` and `EnumFromPy`.
+/// - `From` conversions between `Foo` and `FooUnion`.
+/// - `IntoPy` and `FromPyObject` impls for `Foo` itself that go through `FooUnion`.
+///
+/// Pass `#[complex_enum(module = "solders.transaction_status")]` to set the classes' module.
+/// All field types must be `Clone` and convertible to and from Python.
+///
+/// # Example
+///
+/// ```ignore
+/// #[complex_enum(module = "solders.transaction_status")]
+/// #[derive(Clone)]
+/// pub enum TransactionError {
+///     AccountInUse,
+///     InstructionError(u8, InstructionErrorType),
+///     DuplicateInstruction { index: u8 },
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn complex_enum(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let ComplexEnumArgs { module } = parse_macro_input!(args as ComplexEnumArgs);
+        let ast = parse_target!(item as ItemEnum, "complex_enum");
+        match complex_enum::expand(module, ast) {
+            Ok(expanded) => TokenStream::from(expanded),
+            Err(e) => e.to_compile_error().into(),
+        }
+    })
+}
+
+struct Base58Args {
+    len: syn::LitInt,
+    eq: bool,
+}
+
+impl Parse for Base58Args {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            len: args.required_lit_int("len")?,
+            eq: args.flag("eq")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Add a `from_string` staticmethod and a `to_base58` method for fixed-size byte types.
+///
+/// Takes the expected byte length, e.g. `#[base58_methods(len = 32)]`.
+/// `from_string` raises `ValueError` for invalid base58 or the wrong number of bytes.
+/// The type must implement `From<[u8; len]>` and `AsRef<[u8]>`, and the calling crate
+/// must depend on `bs58`.
+///
+/// With `#[base58_methods(len = 32, eq)]` a `__richcmp__` is also added whose `==` and `!=`
+/// compare the bytes with another object of the same class, a base58 `str` or `bytes`, so
+/// `pubkey == "4Nd1m..."` works. Strings that aren't valid base58 compare unequal, and other
+/// operands return `NotImplemented`. Ordering comparisons raise `TypeError`.
+/// The class then can't have another `__richcmp__`, e.g. from `richcmp_eq_only`.
+#[proc_macro_attribute]
+pub fn base58_methods(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let Base58Args { len, eq } = parse_macro_input!(args as Base58Args);
+        let mut ast = parse_target!(item as ItemImpl, "base58_methods");
+        let mut methods = vec![
+            ImplItem::Verbatim(quote! {
+                /// Build from a base58 string.
+                ///
+                /// Args:
+                ///     s (str): The base58-encoded string.
+                ///
+                /// Returns:
+                ///     The decoded object.
+                ///
+                /// Raises:
+                ///     ValueError: If the string is not valid base58 or decodes to the wrong number of bytes.
+                ///
+                #[staticmethod]
+                pub fn from_string(s: &str) -> pyo3::prelude::PyResult<Self> {
+                    let bytes = bs58::decode(s).into_vec().map_err(|e| {
+                        pyo3::exceptions::PyValueError::new_err(format!("invalid base58 string: {}", e))
+                    })?;
+                    let arr = <[u8; #len]>::try_from(bytes.as_slice()).map_err(|_| {
+                        pyo3::exceptions::PyValueError::new_err(format!(
+                            "expected {} bytes, got {}",
+                            #len,
+                            bytes.len()
+                        ))
+                    })?;
+                    Ok(Self::from(arr))
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                /// Convert to a base58 string.
+                ///
+                /// Returns:
+                ///     str: The base58-encoded string.
+                ///
+                pub fn to_base58(&self) -> String {
+                    bs58::encode(AsRef::<[u8]>::as_ref(self)).into_string()
+                }
+            }),
+        ];
+        if eq {
+            let name = match self_type_ident(&ast) {
+                Ok(name) => name.to_string(),
+                Err(e) => return e.to_compile_error().into(),
+            };
+            let ordering_message = format!("{} only supports == and !=", name);
+            let other = bound::arg(quote! { PyAny });
+            let methods_in_scope = bound::methods_in_scope();
+            methods.push(ImplItem::Verbatim(quote! {
+                pub fn __richcmp__(
+                    &self,
+                    other: #other,
+                    op: pyo3::basic::CompareOp,
+                    py: pyo3::prelude::Python<'_>,
+                ) -> pyo3::prelude::PyResult<pyo3::prelude::PyObject> {
+                    #methods_in_scope
+                    use pyo3::prelude::IntoPy;
+                    let own: &[u8] = AsRef::<[u8]>::as_ref(self);
+                    let equal = if let Ok(other) = other.extract::<pyo3::PyRef<'_, Self>>() {
+                        own == AsRef::<[u8]>::as_ref(&*other)
+                    } else if let Ok(s) = other.downcast::<pyo3::types::PyString>() {
+                        match s.to_str().map(|s| bs58::decode(s).into_vec()) {
+                            Ok(Ok(decoded)) => own == decoded.as_slice(),
+                            _ => false,
+                        }
+                    } else if let Ok(b) = other.downcast::<pyo3::types::PyBytes>() {
+                        own == b.as_bytes()
+                    } else {
+                        return Ok(py.NotImplemented());
+                    };
+                    match op {
+                        pyo3::basic::CompareOp::Eq => Ok(equal.into_py(py)),
+                        pyo3::basic::CompareOp::Ne => Ok((!equal).into_py(py)),
+                        _ => Err(pyo3::exceptions::PyTypeError::new_err(#ordering_message)),
+                    }
+                }
+            }));
+        }
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("base58_methods", &ast))
+    })
+}
+
+/// Add a `__format__` for fixed-size byte types, supporting a few format specs.
+///
+/// - `""` or `"base58"`: the base58 string, e.g. `f"{pubkey}"`.
+/// - `"hex"`: lowercase hex, e.g. `f"{pubkey:hex}"`.
+/// - `"short"`: the first and last four base58 characters, e.g. `f"{sig:short}"` gives `"4Nd1...x3Yz"`.
+///
+/// Other specs raise `ValueError`. The bytes come from `AsRef<[u8]>`, and the calling crate
+/// must depend on `bs58`.
+#[proc_macro_attribute]
+pub fn format_dunder(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        if !args.is_empty() {
+            return errors::at(
+                ErrorKind::UnrecognizedArgument,
+                Span::call_site(),
+                "`format_dunder` takes no arguments",
+            )
+            .to_compile_error()
+            .into();
+        }
+        let mut ast = parse_target!(item as ItemImpl, "format_dunder");
+        ast.items.push(ImplItem::Verbatim(quote! {
+            pub fn __format__(&self, format_spec: &str) -> pyo3::prelude::PyResult<String> {
+                let bytes: &[u8] = AsRef::<[u8]>::as_ref(self);
+                match format_spec {
+                    "" | "base58" => Ok(bs58::encode(bytes).into_string()),
+                    "hex" => Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect()),
+                    "short" => {
+                        let full = bs58::encode(bytes).into_string();
+                        if full.len() <= 11 {
+                            Ok(full)
+                        } else {
+                            Ok(format!("{}...{}", &full[..4], &full[full.len() - 4..]))
+                        }
+                    }
+                    other => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                        "unknown format code '{}', expected 'base58', 'hex' or 'short'",
+                        other
+                    ))),
+                }
+            }
+        }));
+        TokenStream::from(impl_output("format_dunder", &ast))
+    })
+}
+
+/// Add a `to_base64` method and a `from_base64` staticmethod using the `CommonMethods` trait.
+///
+/// The base64 payload is the same serialization used by `__bytes__` and `from_bytes`,
+/// encoded with the standard alphabet and padding.
+/// The calling crate must depend on `base64` 0.21 or later, for its `Engine` API.
+#[proc_macro_attribute]
+pub fn base64_methods(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "base64_methods");
+        let as_bytes = bound::as_bytes(quote! { bytes });
+        let methods = vec![
+            ImplItem::Verbatim(quote! {
+                /// Serialize and encode as base64.
+                ///
+                /// Returns:
+                ///     str: The base64-encoded serialized object.
+                ///
+                pub fn to_base64(&self, py: pyo3::prelude::Python<'_>) -> String {
+                    use base64::Engine;
+                    let bytes = self.pybytes(py);
+                    base64::engine::general_purpose::STANDARD.encode(#as_bytes)
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                /// Deserialize from a base64 string.
+                ///
+                /// Args:
+                ///     data (str): the base64-encoded serialized object.
+                ///
+                /// Returns: the deserialized object.
+                ///
+                #[staticmethod]
+                pub fn from_base64(data: &str) -> pyo3::prelude::PyResult<Self> {
+                    use base64::Engine;
+                    let bytes = base64::engine::general_purpose::STANDARD.decode(data).map_err(|e| {
+                        pyo3::exceptions::PyValueError::new_err(format!("invalid base64 string: {}", e))
+                    })?;
+                    Self::py_from_bytes(&bytes)
+                }
+            }),
+        ];
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("base64_methods", &ast))
+    })
+}
+
+/// Add a `to_borsh` method and a `from_borsh` staticmethod using borsh serialization.
+///
+/// The type must implement `BorshSerialize` and `BorshDeserialize`, and the calling crate
+/// must depend on `borsh` 1.0 or later, for `borsh::to_vec`. Borsh errors are raised as
+/// `ValueError`.
+#[proc_macro_attribute]
+pub fn borsh_methods(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "borsh_methods");
+        let py_bytes = bound::py_bytes();
+        let new_py_bytes = bound::new_py_bytes();
+        let methods = vec![
+            ImplItem::Verbatim(quote! {
+                /// Serialize with borsh.
+                ///
+                /// Returns:
+                ///     bytes: The borsh-serialized object.
+                ///
+                pub fn to_borsh<'a>(&self, py: pyo3::prelude::Python<'a>) -> pyo3::prelude::PyResult<#py_bytes> {
+                    let bytes = borsh::to_vec(self).map_err(|e| {
+                        pyo3::exceptions::PyValueError::new_err(format!("failed to serialize to borsh: {}", e))
+                    })?;
+                    Ok(#new_py_bytes(py, &bytes))
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                /// Deserialize from borsh bytes.
+                ///
+                /// Args:
+                ///     data (bytes): the borsh-serialized object.
+                ///
+                /// Returns: the deserialized object.
+                ///
+                #[staticmethod]
+                pub fn from_borsh(data: &[u8]) -> pyo3::prelude::PyResult<Self> {
+                    <Self as borsh::BorshDeserialize>::try_from_slice(data).map_err(|e| {
+                        pyo3::exceptions::PyValueError::new_err(format!("failed to deserialize from borsh: {}", e))
+                    })
+                }
+            }),
+        ];
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("borsh_methods", &ast))
+    })
+}
+
+/// Add a `to_msgpack` method and a `from_msgpack` staticmethod using MessagePack serialization.
+///
+/// Structs are encoded as maps so the output is self-describing.
+/// The type must implement serde's `Serialize` and `Deserialize`, and the calling crate
+/// must depend on `rmp-serde`. Encoding errors are raised as `ValueError`.
+#[proc_macro_attribute]
+pub fn msgpack_methods(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "msgpack_methods");
+        let py_bytes = bound::py_bytes();
+        let new_py_bytes = bound::new_py_bytes();
+        let methods = vec![
+            ImplItem::Verbatim(quote! {
+                /// Serialize with MessagePack.
+                ///
+                /// Returns:
+                ///     bytes: The MessagePack-encoded object.
+                ///
+                pub fn to_msgpack<'a>(&self, py: pyo3::prelude::Python<'a>) -> pyo3::prelude::PyResult<#py_bytes> {
+                    let bytes = rmp_serde::to_vec_named(self).map_err(|e| {
+                        pyo3::exceptions::PyValueError::new_err(format!("failed to serialize to MessagePack: {}", e))
+                    })?;
+                    Ok(#new_py_bytes(py, &bytes))
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                /// Deserialize from MessagePack bytes.
+                ///
+                /// Args:
+                ///     data (bytes): the MessagePack-encoded object.
+                ///
+                /// Returns: the deserialized object.
+                ///
+                #[staticmethod]
+                pub fn from_msgpack(data: &[u8]) -> pyo3::prelude::PyResult<Self> {
+                    rmp_serde::from_slice(data).map_err(|e| {
+                        pyo3::exceptions::PyValueError::new_err(format!("failed to deserialize from MessagePack: {}", e))
+                    })
+                }
+            }),
+        ];
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("msgpack_methods", &ast))
+    })
+}
+
+/// Add a `to_dict` method and a `from_dict` staticmethod that convert directly to and from
+/// native Python objects, skipping the intermediate JSON string.
+///
+/// The output matches `json.loads(obj.to_json())`.
+/// The type must implement serde's `Serialize` and `Deserialize`, and the calling crate
+/// must depend on `pythonize`.
+#[proc_macro_attribute]
+pub fn dict_methods(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "dict_methods");
+        let raw = bound::arg(quote! { PyAny });
+        let methods = vec![
+            ImplItem::Verbatim(quote! {
+                /// Convert to a dictionary of native Python objects.
+                ///
+                /// Returns:
+                ///     dict: The object as a dict.
+                ///
+                pub fn to_dict(&self, py: pyo3::prelude::Python<'_>) -> pyo3::prelude::PyResult<pyo3::prelude::PyObject> {
+                    Ok(pythonize::pythonize(py, self)?.into())
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                /// Build from a dictionary of native Python objects.
+                ///
+                /// Args:
+                ///     raw (dict): The object as a dict, as returned by ``to_dict``.
+                ///
+                /// Returns: the deserialized object.
+                ///
+                #[staticmethod]
+                pub fn from_dict(raw: #raw) -> pyo3::prelude::PyResult<Self> {
+                    Ok(pythonize::depythonize(raw)?)
+                }
+            }),
+        ];
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("dict_methods", &ast))
+    })
+}
+
+/// Add a `to_yaml` method and a `from_yaml` staticmethod.
+///
+/// The type must implement serde's `Serialize` and `Deserialize`, and the calling crate
+/// must depend on `serde_yaml`. YAML errors are raised as `ValueError`.
+#[proc_macro_attribute]
+pub fn yaml_methods(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "yaml_methods");
+        let methods = vec![
+            ImplItem::Verbatim(quote! {
+                /// Convert to a YAML string.
+                pub fn to_yaml(&self) -> pyo3::prelude::PyResult<String> {
+                    serde_yaml::to_string(self).map_err(|e| {
+                        pyo3::exceptions::PyValueError::new_err(format!("failed to serialize to YAML: {}", e))
+                    })
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                /// Build from a YAML string.
+                #[staticmethod]
+                pub fn from_yaml(raw: &str) -> pyo3::prelude::PyResult<Self> {
+                    serde_yaml::from_str(raw).map_err(|e| {
+                        pyo3::exceptions::PyValueError::new_err(format!("failed to deserialize from YAML: {}", e))
+                    })
+                }
+            }),
+        ];
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("yaml_methods", &ast))
+    })
+}
+
+/// Add a `schema` classmethod returning the type's JSON Schema as a dict.
+///
+/// The type must implement `schemars::JsonSchema`, and the calling crate must depend on
+/// `schemars` and `pythonize`.
+#[proc_macro_attribute]
+pub fn json_schema(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "json_schema");
+        let cls = bound::arg(quote! { PyType });
+        ast.items.push(ImplItem::Verbatim(quote! {
+            /// Get the JSON Schema for this class.
+            ///
+            /// Returns:
+            ///     dict: The JSON Schema.
+            ///
+            #[classmethod]
+            pub fn schema(_cls: #cls, py: pyo3::prelude::Python<'_>) -> pyo3::prelude::PyResult<pyo3::prelude::PyObject> {
+                let schema = schemars::schema_for!(Self);
+                Ok(pythonize::pythonize(py, &schema)?.into())
+            }
+        }));
+        TokenStream::from(impl_output("json_schema", &ast))
+    })
+}
+
+/// Add a `from_json_file` staticmethod and a `write_json_file` method using the `CommonMethods` trait.
+///
+/// I/O errors are raised as the matching `OSError` subclass and invalid JSON
+/// is raised however `py_from_json` raises it.
+#[proc_macro_attribute]
+pub fn json_file_methods(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "json_file_methods");
+        let methods = vec![
+            ImplItem::Verbatim(quote! {
+                /// Read from a JSON file.
+                ///
+                /// Args:
+                ///     path (str | os.PathLike): The file to read.
+                ///
+                /// Returns: the deserialized object.
+                ///
+                #[staticmethod]
+                pub fn from_json_file(path: std::path::PathBuf) -> pyo3::prelude::PyResult<Self> {
+                    let raw = std::fs::read_to_string(path)?;
+                    Self::py_from_json(&raw)
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                /// Write to a JSON file, overwriting it if it exists.
+                ///
+                /// Args:
+                ///     path (str | os.PathLike): The file to write.
+                ///
+                pub fn write_json_file(&self, path: std::path::PathBuf) -> pyo3::prelude::PyResult<()> {
+                    std::fs::write(path, self.py_to_json())?;
+                    Ok(())
+                }
+            }),
+        ];
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("json_file_methods", &ast))
+    })
+}
+
+/// Add legacy and versioned byte serialization methods and a `version` getter using the `VersionedMethods` trait.
+///
+/// Adds `to_bytes_versioned`, `from_bytes_versioned`, `to_bytes_legacy`, `from_bytes_legacy` and `version`,
+/// delegating to `pybytes_versioned`, `py_from_bytes_versioned`, `pybytes_legacy`,
+/// `py_from_bytes_legacy` and `pyversion` respectively.
+#[proc_macro_attribute]
+pub fn versioned_methods(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "versioned_methods");
+        let py_bytes = bound::py_bytes();
+        let methods = vec![
+            ImplItem::Verbatim(quote! {
+                /// Serialize in the versioned format.
+                ///
+                /// Returns:
+                ///     bytes: The serialized object, prefixed with its version.
+                ///
+                pub fn to_bytes_versioned<'a>(&self, py: pyo3::prelude::Python<'a>) -> #py_bytes {
+                    self.pybytes_versioned(py)
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                /// Deserialize from the versioned format.
+                ///
+                /// Args:
+                ///     data (bytes): the serialized object, prefixed with its version.
+                ///
+                /// Returns: the deserialized object.
+                ///
+                #[staticmethod]
+                pub fn from_bytes_versioned(data: &[u8]) -> pyo3::prelude::PyResult<Self> {
+                    Self::py_from_bytes_versioned(data)
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                /// Serialize in the legacy format.
+                ///
+                /// Returns:
+                ///     bytes: The serialized object.
+                ///
+                pub fn to_bytes_legacy<'a>(&self, py: pyo3::prelude::Python<'a>) -> #py_bytes {
+                    self.pybytes_legacy(py)
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                /// Deserialize from the legacy format.
+                ///
+                /// Args:
+                ///     data (bytes): the serialized object.
+                ///
+                /// Returns: the deserialized object.
+                ///
+                #[staticmethod]
+                pub fn from_bytes_legacy(data: &[u8]) -> pyo3::prelude::PyResult<Self> {
+                    Self::py_from_bytes_legacy(data)
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                /// The serialization version of this object.
+                #[getter]
+                pub fn version(&self, py: pyo3::prelude::Python<'_>) -> pyo3::prelude::PyObject {
+                    self.pyversion(py)
+                }
+            }),
+        ];
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("versioned_methods", &ast))
+    })
+}
+
+/// Add `__str__` and a `from_string` staticmethod using the `Display` and `FromStr` traits.
+///
+/// `FromStr` errors are raised as `ValueError` using the error's `Display` output.
+#[proc_macro_attribute]
+pub fn str_methods(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as ItemImpl, "str_methods");
+        let methods = vec![
+            ImplItem::Verbatim(quote! { pub fn __str__(&self) -> String {self.to_string()} }),
+            ImplItem::Verbatim(quote! {
+                /// Build from a string.
+                ///
+                /// Args:
+                ///     s (str): The string representation.
+                ///
+                /// Returns:
+                ///     The parsed object.
+                ///
+                /// Raises:
+                ///     ValueError: If the string is invalid.
+                ///
+                #[staticmethod]
+                pub fn from_string(s: &str) -> pyo3::prelude::PyResult<Self> {
+                    s.parse::<Self>()
+                        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+                }
+            }),
+        ];
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("str_methods", &ast))
+    })
+}
+
+struct DebugReprArgs {
+    wrap: bool,
+}
+
+impl Parse for DebugReprArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            wrap: args.flag("wrap")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Add a `__repr__` using the `Debug` trait, for types that don't need the rest of `CommonMethods`.
+///
+/// With `#[debug_repr(wrap)]` the `Debug` output is wrapped in the class name,
+/// constructor-style, which suits newtypes whose `Debug` only prints the inner value.
+///
+/// # Example
+///
+#[cfg_attr(not(feature = "multiple-pymethods"), doc = "```rust")]
+#[cfg_attr(feature = "multiple-pymethods", doc = "```ignore")]
+/// use solders_macros::debug_repr;
+///
+/// struct Foo(u8);
+///
+/// impl std::fmt::Debug for Foo {
+///   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///     write!(f, "{}", self.0)
+///   }
+/// }
+///
+/// #[debug_repr(wrap)]
+/// impl Foo {}
+///
+/// assert_eq!("Foo(3)", Foo(3).__repr__());
+/// ```
+#[proc_macro_attribute]
+pub fn debug_repr(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let DebugReprArgs { wrap } = parse_macro_input!(args as DebugReprArgs);
+        let mut ast = parse_target!(item as ItemImpl, "debug_repr");
+        let body = if wrap {
+            let name = match self_type_ident(&ast) {
+                Ok(name) => name.to_string(),
+                Err(e) => return e.to_compile_error().into(),
+            };
+            quote! { format!("{}({:?})", #name, self) }
+        } else {
+            quote! { format!("{:?}", self) }
+        };
+        ast.items.push(ImplItem::Verbatim(
+            quote! { pub fn __repr__(&self) -> String {#body} },
+        ));
+        TokenStream::from(impl_output("debug_repr", &ast))
+    })
+}
+
+/// Add `__setattr__` and `__delattr__` that always raise `AttributeError`, making the object
+/// immutable from Python.
+///
+/// The error names the class and the attribute and suggests `replace()` for making a
+/// modified copy; see `replace_methods`. This also stops `#[setter]`s from working,
+/// so only use it on classes without them.
+///
+/// # Example
+///
+/// ```ignore
+/// #[immutable]
+/// #[pymethods]
+/// impl Pubkey {}
+/// ```
+#[proc_macro_attribute]
+pub fn immutable(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        if !args.is_empty() {
+            return errors::at(
+                ErrorKind::UnrecognizedArgument,
+                Span::call_site(),
+                "`immutable` takes no arguments",
+            )
+            .to_compile_error()
+            .into();
+        }
+        let mut ast = parse_target!(item as ItemImpl, "immutable");
+        let name = match self_type_ident(&ast) {
+            Ok(name) => name.to_string(),
+            Err(e) => return e.to_compile_error().into(),
+        };
+        let set_message = format!(
+            "cannot set attribute '{{}}' of immutable {} object; use replace() to make a modified copy",
+            name
+        );
+        let del_message = format!(
+            "cannot delete attribute '{{}}' of immutable {} object",
+            name
+        );
+        let value = bound::arg(quote! { PyAny });
+        let methods = vec![
+            ImplItem::Verbatim(quote! {
+                pub fn __setattr__(&self, name: &str, _value: #value) -> pyo3::prelude::PyResult<()> {
+                    Err(pyo3::exceptions::PyAttributeError::new_err(format!(#set_message, name)))
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                pub fn __delattr__(&self, name: &str) -> pyo3::prelude::PyResult<()> {
+                    Err(pyo3::exceptions::PyAttributeError::new_err(format!(#del_message, name)))
+                }
+            }),
+        ];
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("immutable", &ast))
+    })
+}
+
+struct DeprecatedAliasArgs {
+    old: syn::LitStr,
+    new: syn::LitStr,
+    since: Option<syn::LitStr>,
+}
+
+impl Parse for DeprecatedAliasArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            old: args.required_lit_str("old")?,
+            new: args.required_lit_str("new")?,
+            since: args.lit_str("since")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+fn is_pyresult(output: &syn::ReturnType) -> bool {
+    match output {
+        syn::ReturnType::Type(_, ty) => match &**ty {
+            Type::Path(p) => p
+                .path
+                .segments
+                .last()
+                .is_some_and(|seg| seg.ident == "PyResult"),
+            _ => false,
+        },
+        syn::ReturnType::Default => false,
+    }
+}
+
+/// Add a deprecated alias for a renamed method.
+///
+/// `#[deprecated_alias(old = "serialize", new = "to_bytes", since = "0.15")]` adds a `serialize`
+/// method with the same signature as `to_bytes` that emits a Python `DeprecationWarning` and then
+/// calls `to_bytes`. `since` is optional. The alias always returns a `PyResult`, since the warning
+/// itself can raise when warnings are turned into errors.
+///
+/// Apply the attribute once per alias.
+#[proc_macro_attribute]
+pub fn deprecated_alias(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let DeprecatedAliasArgs { old, new, since } =
+            parse_macro_input!(args as DeprecatedAliasArgs);
+        let mut ast = parse_target!(item as ItemImpl, "deprecated_alias");
+        let target = ast.items.iter().find_map(|item| match item {
+            ImplItem::Method(m) if m.sig.ident == new.value() => Some(m),
+            _ => None,
+        });
+        let target = match target {
+            Some(t) => t,
+            None => {
+                return errors::spanned(
+                    ErrorKind::InvalidArgument,
+                    &new,
+                    format!("no method named `{}` in this impl", new.value()),
+                )
+                .to_compile_error()
+                .into()
+            }
+        };
+        let mut call_args = Vec::new();
+        for arg in &target.sig.inputs {
+            match arg {
+                syn::FnArg::Receiver(_) => call_args.push(quote! {self}),
+                syn::FnArg::Typed(pat_type) => match &*pat_type.pat {
+                    syn::Pat::Ident(pat_ident) => {
+                        let ident = &pat_ident.ident;
+                        call_args.push(quote! {#ident});
+                    }
+                    other => {
+                        return errors::spanned(
+                            ErrorKind::InvalidArgument,
+                            other,
+                            "expected a plain parameter name",
+                        )
+                        .to_compile_error()
+                        .into()
+                    }
+                },
+            }
+        }
+        let target_ident = &target.sig.ident;
+        let mut sig = target.sig.clone();
+        sig.ident = Ident::new(&old.value(), old.span());
+        let call = quote! { Self::#target_ident(#(#call_args),*) };
+        let body = if is_pyresult(&target.sig.output) {
+            call
+        } else {
+            let ret = match &target.sig.output {
+                syn::ReturnType::Type(_, ty) => quote! {#ty},
+                syn::ReturnType::Default => quote! {()},
+            };
+            sig.output = syn::parse_quote! { -> pyo3::prelude::PyResult<#ret> };
+            quote! { Ok(#call) }
+        };
+        let message = match &since {
+            Some(since) => format!(
+                "`{}` is deprecated since {}, use `{}` instead.",
+                old.value(),
+                since.value(),
+                new.value()
+            ),
+            None => format!(
+                "`{}` is deprecated, use `{}` instead.",
+                old.value(),
+                new.value()
+            ),
+        };
+        let doc = format!(" Deprecated alias of ``{}``.", new.value());
+        let attrs = target.attrs.iter().filter(|a| !a.path.is_ident("doc"));
+        let warn = if cfg!(feature = "bound") {
+            quote! {
+                pyo3::PyErr::warn_bound(py, py.get_type_bound::<pyo3::exceptions::PyDeprecationWarning>().as_any(), #message, 1)
+            }
+        } else {
+            quote! {
+                pyo3::PyErr::warn(py, py.get_type::<pyo3::exceptions::PyDeprecationWarning>(), #message, 1)
+            }
+        };
+        ast.items.push(ImplItem::Verbatim(quote! {
+            #[doc = #doc]
+            #(#attrs)*
+            pub #sig {
+                pyo3::prelude::Python::with_gil(|py| {
+                    #warn
+                })?;
+                #body
+            }
+        }));
+        TokenStream::from(impl_output("deprecated_alias", &ast))
+    })
+}
+
+struct PyExceptionsArgs {
+    module: Option<syn::LitStr>,
+}
+
+impl Parse for PyExceptionsArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            module: args.lit_str("module")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// A dotted module name as the `a.b` expression `create_exception!` stringifies.
+fn dotted_module(module: &syn::LitStr) -> syn::Result<proc_macro2::TokenStream> {
+    let parts = module
+        .value()
+        .split('.')
+        .map(|part| {
+            syn::parse_str::<Ident>(part).map(|ident| Ident::new(&ident.to_string(), module.span()))
+        })
+        .collect::<syn::Result<Vec<Ident>>>()
+        .map_err(|_| {
+            errors::spanned(
+                ErrorKind::InvalidArgument,
+                module,
+                "expected a dotted module name, e.g. `module = \"solders.errors\"`",
+            )
+        })?;
+    Ok(quote! { #(#parts).* })
+}
+
+/// Generate a Python exception class for every variant of an error enum.
+///
+/// For an enum `FooError`, this adds a Rust module `foo_error_exceptions` containing:
+///
+/// - A `FooError` exception deriving from `Exception`.
+/// - One exception per variant, named after the variant and deriving from `FooError`.
+///
+/// It also adds `From<FooError> for PyErr`, raising the variant's exception with the enum's
+/// `Display` output as the message, and a `FooError::register_exceptions(py, m)` function
+/// that adds all the classes to a Python module.
+///
+/// The exceptions' `__module__` is `solders`; pass e.g. `#[py_exceptions(module = "solders.errors")]`
+/// to change it. No variant can have the same name as the enum, since their exceptions would clash.
+///
+/// # Example
+///
+/// ```ignore
+/// #[py_exceptions]
+/// #[derive(Debug, thiserror::Error)]
+/// pub enum SignerError {
+///     #[error("keypair-pubkey mismatch")]
+///     KeypairPubkeyMismatch,
+///     #[error("not enough signers")]
+///     NotEnoughSigners,
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn py_exceptions(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let PyExceptionsArgs { module } = parse_macro_input!(args as PyExceptionsArgs);
+        let module = match module {
+            Some(module) => match dotted_module(&module) {
+                Ok(module) => module,
+                Err(e) => return e.to_compile_error().into(),
+            },
+            None => quote! { solders },
+        };
+        let mut new_stream = proc_macro2::TokenStream::from(item.clone());
+        let ast = parse_target!(item as ItemEnum, "py_exceptions");
+        let enum_name = &ast.ident;
+        if let Some(variant) = ast.variants.iter().find(|v| v.ident == *enum_name) {
+            return errors::spanned(
+                ErrorKind::WrongItem,
+                &variant.ident,
+                format!(
+                    "variant `{}` has the same name as the enum, so their Python exceptions would clash; rename the variant",
+                    enum_name
+                ),
+            )
+            .to_compile_error()
+            .into();
+        }
+        let mod_name = format_ident!(
+            "{}_exceptions",
+            RenameRule::SnakeCase.apply(&enum_name.to_string())
+        );
+        let variant_names: Vec<&Ident> = ast.variants.iter().map(|v| &v.ident).collect();
+        let m = bound::arg(quote! { PyModule });
+        let methods_in_scope = bound::methods_in_scope();
+        let enum_type = bound::get_type(quote! { py }, quote! { #mod_name::#enum_name });
+        let variant_types = variant_names
+            .iter()
+            .map(|v| bound::get_type(quote! { py }, quote! { #mod_name::#v }));
+        let generated = quote! {
+            pub mod #mod_name {
+                pyo3::create_exception!(#module, #enum_name, pyo3::exceptions::PyException);
+                #(pyo3::create_exception!(#module, #variant_names, #enum_name);)*
+            }
+
+            impl From<#enum_name> for pyo3::PyErr {
+                fn from(e: #enum_name) -> Self {
+                    let msg = e.to_string();
+                    match e {
+                        #(#enum_name::#variant_names { .. } => #mod_name::#variant_names::new_err(msg)),*
+                    }
+                }
+            }
+
+            impl #enum_name {
+                /// Add this error's Python exception classes to a module.
+                pub fn register_exceptions(py: pyo3::prelude::Python<'_>, m: #m) -> pyo3::prelude::PyResult<()> {
+                    #methods_in_scope
+                    m.add(stringify!(#enum_name), #enum_type)?;
+                    #(m.add(stringify!(#variant_names), #variant_types)?;)*
+                    Ok(())
+                }
+            }
+        };
+        new_stream.extend(generated);
+        TokenStream::from(new_stream)
+    })
+}
+
+/// The exception and message format from a `#[pyerr(...)]` attribute.
+struct PyErrAttr {
+    exception: proc_macro2::TokenStream,
+    debug: bool,
+}
+
+impl PyErrAttr {
+    fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Option<Self>> {
+        let attr = match attrs.iter().find(|a| a.path.is_ident("pyerr")) {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        let mut args: MacroArgs = attr.parse_args()?;
+        let debug = args.flag("debug")?;
+        let custom = args.ty("custom")?;
+        let builtin = args.any_flag();
+        args.finish()?;
+        let exception = match (custom, builtin) {
+            (Some(custom), None) => quote! {#custom},
+            (None, Some(builtin)) => {
+                let ident = format_ident!("Py{}", builtin, span = builtin.span());
+                quote! {pyo3::exceptions::#ident}
+            }
+            _ => {
+                return Err(errors::spanned(
+                    ErrorKind::InvalidArgument,
+                    attr,
+                    "expected exactly one of a builtin exception name like `ValueError` or `custom = SomeError`",
+                ))
+            }
+        };
+        Ok(Some(Self { exception, debug }))
+    }
+}
+
+/// Derive `From<Self> for PyErr`, choosing the Python exception per variant.
+///
+/// Annotate variants with `#[pyerr(ValueError)]` for a builtin exception
+/// (`pyo3::exceptions::PyValueError` here) or `#[pyerr(custom = SerdeJSONError)]` for an exception
+/// type created with `create_exception!`. An attribute on the enum itself sets the default for
+/// variants without one; otherwise the default is `Exception`.
+///
+/// The message is the error's `Display` output, or its `Debug` output with `#[pyerr(..., debug)]`.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(Debug, thiserror::Error, IntoPyErr)]
+/// #[pyerr(ValueError)]
+/// pub enum PyErrWrapper {
+///     #[error(transparent)]
+///     #[pyerr(custom = SerdeJSONError)]
+///     SerdeJson(#[from] serde_json::Error),
+///     #[error("{0}")]
+///     Parse(String),
+/// }
+/// ```
+#[proc_macro_derive(IntoPyErr, attributes(pyerr))]
+pub fn into_py_err(item: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(item as syn::DeriveInput);
+    match into_py_err_impl(&ast) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn into_py_err_impl(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+    let data = match &ast.data {
+        syn::Data::Enum(data) => data,
+        _ => {
+            return Err(errors::spanned(
+                ErrorKind::WrongItem,
+                &ast.ident,
+                "expected an enum",
+            ))
+        }
+    };
+    let default = PyErrAttr::from_attrs(&ast.attrs)?.unwrap_or(PyErrAttr {
+        exception: quote! {pyo3::exceptions::PyException},
+        debug: false,
+    });
+    let mut arms = Vec::new();
+    for variant in &data.variants {
+        let variant_name = &variant.ident;
+        let attr = PyErrAttr::from_attrs(&variant.attrs)?;
+        let PyErrAttr { exception, debug } = attr.as_ref().unwrap_or(&default);
+        let msg = if *debug {
+            quote! { format!("{:?}", e) }
+        } else {
+            quote! { e.to_string() }
+        };
+        arms.push(quote! { #name::#variant_name { .. } => <#exception>::new_err(#msg) });
+    }
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics From<#name #ty_generics> for pyo3::PyErr #where_clause {
+            fn from(e: #name #ty_generics) -> Self {
+                match &e {
+                    #(#arms),*
+                }
+            }
+        }
+    })
+}
+
+struct SignerMethodsArgs {
+    signer_trait: Type,
+}
+
+impl Parse for SignerMethodsArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            signer_trait: args
+                .ty("signer_trait")?
+                .unwrap_or_else(|| syn::parse_quote!(solana_sdk::signer::Signer)),
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Add the Python signer interface using the Rust `Signer` trait.
+///
+/// Adds `pubkey`, `sign_message` and `is_interactive`, delegating to the type's
+/// `solana_sdk::signer::Signer` impl, plus the `__richcmp__` from `richcmp_signer`.
+/// Pass e.g. `#[signer_methods(signer_trait = solana_signer::Signer)]` to use another trait.
+#[proc_macro_attribute]
+pub fn signer_methods(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let SignerMethodsArgs { signer_trait } = parse_macro_input!(args as SignerMethodsArgs);
+        let mut ast = parse_target!(item as ItemImpl, "signer_methods");
+        let methods = vec![
+            ImplItem::Verbatim(quote! {
+                /// Get this signer's public key.
+                ///
+                /// Returns:
+                ///     Pubkey: The pubkey.
+                ///
+                pub fn pubkey(&self) -> __solders_crate::Pubkey {
+                    <Self as #signer_trait>::pubkey(self).into()
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                /// Sign a message with this signer.
+                ///
+                /// Args:
+                ///     message (bytes): The message to sign.
+                ///
+                /// Returns:
+                ///     Signature: The signature.
+                ///
+                pub fn sign_message(&self, message: &[u8]) -> __solders_crate::Signature {
+                    <Self as #signer_trait>::sign_message(self, message).into()
+                }
+            }),
+            ImplItem::Verbatim(quote! {
+                /// Whether the signer needs user interaction to sign.
+                ///
+                /// Returns:
+                ///     bool: ``True`` if the signer is interactive.
+                ///
+                pub fn is_interactive(&self) -> bool {
+                    <Self as #signer_trait>::is_interactive(self)
+                }
+            }),
+            ImplItem::Verbatim(
+                quote! {pub fn __richcmp__(&self, other: __solders_crate::Signer, op: pyo3::basic::CompareOp) -> pyo3::prelude::PyResult<bool> {self.richcmp(other, op)}},
+            ),
+        ];
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("signer_methods", &ast))
+    })
+}
+
+struct ContextManagerArgs {
+    open: Option<Ident>,
+    close: Option<Ident>,
+    async_open: Option<Ident>,
+    async_close: Option<Ident>,
+}
+
+impl Parse for ContextManagerArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            open: args.ident("open")?,
+            close: args.ident("close")?,
+            async_open: args.ident("async_open")?,
+            async_close: args.ident("async_close")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Add context manager dunders that delegate to the type's open and close methods.
+///
+/// `#[context_manager(open = start, close = stop)]` adds `__enter__`, which calls
+/// `self.start()` and returns the object, and `__exit__`, which calls `self.stop()`.
+/// Both methods must take `&mut self` and return `PyResult<()>`. Either can be omitted.
+///
+/// `async_open` and `async_close` add `__aenter__` and `__aexit__` in the same way.
+/// These methods take `&mut self` and a `Python` token and must return a Python awaitable,
+/// e.g. one made with `pyo3_asyncio`, as a `PyResult<&PyAny>`
+/// (`PyResult<Bound<PyAny>>` with the `bound` feature).
+///
+/// Exceptions raised inside the `with` block are never suppressed.
+#[proc_macro_attribute]
+pub fn context_manager(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let ContextManagerArgs {
+            open,
+            close,
+            async_open,
+            async_close,
+        } = parse_macro_input!(args as ContextManagerArgs);
+        let mut ast = parse_target!(item as ItemImpl, "context_manager");
+        let any = bound::arg(quote! { PyAny });
+        let awaitable = bound::owned(quote! { PyAny });
+        let mut methods = Vec::new();
+        if open.is_some() || close.is_some() {
+            let open_call = open.map(|open| quote! { slf.#open()?; });
+            let close_call = close.map(|close| quote! { self.#close()?; });
+            methods.push(ImplItem::Verbatim(quote! {
+                pub fn __enter__(mut slf: pyo3::PyRefMut<'_, Self>) -> pyo3::prelude::PyResult<pyo3::PyRefMut<'_, Self>> {
+                    #open_call
+                    Ok(slf)
+                }
+            }));
+            methods.push(ImplItem::Verbatim(quote! {
+                pub fn __exit__(
+                    &mut self,
+                    _exc_type: #any,
+                    _exc_value: #any,
+                    _traceback: #any,
+                ) -> pyo3::prelude::PyResult<bool> {
+                    #close_call
+                    Ok(false)
+                }
+            }));
+        }
+        if let Some(async_open) = async_open {
+            methods.push(ImplItem::Verbatim(quote! {
+                pub fn __aenter__<'a>(&mut self, py: pyo3::prelude::Python<'a>) -> pyo3::prelude::PyResult<#awaitable> {
+                    self.#async_open(py)
+                }
+            }));
+        }
+        if let Some(async_close) = async_close {
+            methods.push(ImplItem::Verbatim(quote! {
+                pub fn __aexit__<'a>(
+                    &mut self,
+                    py: pyo3::prelude::Python<'a>,
+                    _exc_type: #any,
+                    _exc_value: #any,
+                    _traceback: #any,
+                ) -> pyo3::prelude::PyResult<#awaitable> {
+                    self.#async_close(py)
+                }
+            }));
+        }
+        if methods.is_empty() {
+            return errors::at(
+                ErrorKind::MissingArgument,
+                Span::call_site(),
+                "expected at least one of `open`, `close`, `async_open` or `async_close`",
+            )
+            .to_compile_error()
+            .into();
+        }
+        ast.items.extend_from_slice(&methods);
+        TokenStream::from(impl_output("context_manager", &ast))
+    })
+}
+
+struct AllGettersArgs {
+    exclude: Vec<Ident>,
+}
+
+impl Parse for AllGettersArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            exclude: args.ident_list("exclude")?.unwrap_or_default(),
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Add a `#[getter]` for every field of a struct.
+///
+/// Each getter returns a clone of the field. To convert the field, annotate it with
+/// `#[getter_into(SomeType)]` and the getter returns `SomeType` via `Into`.
+/// Field doc comments become the getter docs.
+/// Skip fields that need a hand-written getter with `#[all_getters(exclude(a, b))]`.
+///
+/// With the `wasm` feature each getter also gets a `#[wasm_bindgen]` counterpart with a
+/// camelCase name, e.g. `isSigner`.
+///
+/// Apply this to the struct definition. The getters go in a new `#[pymethods]` block, so
+/// if the struct has other `#[pymethods]` blocks you need pyo3's `multiple-pymethods` feature.
+///
+/// # Example
+///
+/// ```ignore
+/// #[all_getters(exclude(inner))]
+/// #[pyclass]
+/// #[derive(Clone)]
+/// pub struct AccountMeta {
+///     /// Pubkey: The account's public key.
+///     #[getter_into(Pubkey)]
+///     pubkey: solana_sdk::pubkey::Pubkey,
+///     /// bool: Whether the account is a signer.
+///     is_signer: bool,
+///     inner: SomethingElse,
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn all_getters(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let AllGettersArgs { exclude } = parse_macro_input!(args as AllGettersArgs);
+        let mut ast = parse_target!(item as syn::ItemStruct, "all_getters");
+        let mut getters = Vec::new();
+        let mut wasm_getters = Vec::new();
+        let fields = match &mut ast.fields {
+            syn::Fields::Named(fields) => fields,
+            _ => {
+                return errors::spanned(
+                    ErrorKind::WrongItem,
+                    &ast.ident,
+                    "expected a struct with named fields",
+                )
+                .to_compile_error()
+                .into()
+            }
+        };
+        for field in fields.named.iter_mut() {
+            let into = match field
+                .attrs
+                .iter()
+                .position(|a| a.path.is_ident("getter_into"))
+            {
+                Some(idx) => match field.attrs.remove(idx).parse_args::<Type>() {
+                    Ok(ty) => Some(ty),
+                    Err(e) => return e.to_compile_error().into(),
+                },
+                None => None,
+            };
+            let name = field.ident.as_ref().unwrap();
+            if exclude.contains(name) {
+                continue;
+            }
+            let docs: Vec<&syn::Attribute> = field
+                .attrs
+                .iter()
+                .filter(|a| a.path.is_ident("doc"))
+                .collect();
+            let field_type = &field.ty;
+            if cfg!(feature = "wasm") {
+                let (ty, body) = match &into {
+                    Some(into) => (into, quote! { self.#name.clone().into() }),
+                    None => (field_type, quote! { self.#name.clone() }),
+                };
+                wasm_getters.push(wasm::getter(&docs, name, ty, body));
+            }
+            let getter = match into {
+                Some(into) => quote! {
+                    #(#docs)*
+                    #[getter]
+                    pub fn #name(&self) -> #into {
+                        self.#name.clone().into()
+                    }
+                },
+                None => quote! {
+                    #(#docs)*
+                    #[getter]
+                    pub fn #name(&self) -> #field_type {
+                        self.#name.clone()
+                    }
+                },
+            };
+            getters.push(getter);
+        }
+        let name = &ast.ident;
+        let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+        let mut expanded = quote! {
+            #ast
+
+            #[pyo3::pymethods]
+            impl #impl_generics #name #ty_generics #where_clause {
+                #(#getters)*
+            }
+        };
+        if cfg!(feature = "wasm") {
+            let self_ty: Type = syn::parse_quote!(#name #ty_generics);
+            match wasm::impl_block(&self_ty, &ast.generics, "all_getters", &wasm_getters) {
+                Ok(block) => expanded.extend(block),
+                Err(e) => expanded.extend(e.to_compile_error()),
+            }
+        }
+        TokenStream::from(expanded)
+    })
+}
+
+/// One `name: Type = self.path.to.field` entry in `inner_getters`.
+struct InnerGetter {
+    docs: Vec<syn::Attribute>,
+    name: Ident,
+    ty: Type,
+    expr: syn::Expr,
+}
+
+impl Parse for InnerGetter {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let docs = input.call(syn::Attribute::parse_outer)?;
+        if let Some(attr) = docs.iter().find(|a| !a.path.is_ident("doc")) {
+            return Err(errors::spanned(
+                ErrorKind::InvalidArgument,
+                attr,
+                "only doc comments are allowed here",
+            ));
+        }
+        let name = input.parse()?;
+        input.parse::<syn::Token![:]>()?;
+        let ty = input.parse()?;
+        input.parse::<syn::Token![=]>()?;
+        let expr = input.parse()?;
+        Ok(Self {
+            docs,
+            name,
+            ty,
+            expr,
+        })
+    }
+}
+
+struct InnerGettersArgs(syn::punctuated::Punctuated<InnerGetter, syn::Token![,]>);
+
+impl Parse for InnerGettersArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self(syn::punctuated::Punctuated::parse_terminated(input)?))
+    }
+}
+
+/// Add getters that read a value out of a wrapped `solana_sdk` type and convert it with `Into`.
+///
+/// Each entry is `name: ReturnType = expression`, optionally preceded by doc comments
+/// that become the getter's docstring.
+///
+/// With the `wasm` feature each getter also gets a `#[wasm_bindgen]` counterpart with a
+/// camelCase name.
+///
+/// # Example
+///
+/// ```ignore
+/// #[inner_getters(
+///     /// Hash: The blockhash included in the transaction.
+///     recent_blockhash: Hash = self.0.message.recent_blockhash,
+///     /// int: The number of required signatures.
+///     num_required_signatures: u8 = self.0.message.header.num_required_signatures,
+/// )]
+/// #[pymethods]
+/// impl Transaction {}
+/// ```
+#[proc_macro_attribute]
+pub fn inner_getters(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let InnerGettersArgs(getters) = parse_macro_input!(args as InnerGettersArgs);
+        let mut ast = parse_target!(item as ItemImpl, "inner_getters");
+        let mut wasm_getters = Vec::new();
+        for InnerGetter {
+            docs,
+            name,
+            ty,
+            expr,
+        } in getters
+        {
+            if cfg!(feature = "wasm") {
+                let docs: Vec<&syn::Attribute> = docs.iter().collect();
+                let body = quote! { (#expr).clone().into() };
+                wasm_getters.push(wasm::getter(&docs, &name, &ty, body));
+            }
+            ast.items.push(ImplItem::Verbatim(quote! {
+                #(#docs)*
+                #[getter]
+                pub fn #name(&self) -> #ty {
+                    (#expr).clone().into()
+                }
+            }));
+        }
+        let mut new_stream = impl_output("inner_getters", &ast);
+        if cfg!(feature = "wasm") {
+            match wasm::impl_block(&ast.self_ty, &ast.generics, "inner_getters", &wasm_getters) {
+                Ok(block) => new_stream.extend(block),
+                Err(e) => new_stream.extend(e.to_compile_error()),
+            }
+        }
+        TokenStream::from(new_stream)
+    })
+}
+
+/// Add a `#[getter]` and `#[setter]` pair for every field of a struct.
+///
+/// Annotate a field with `#[getset(validate_with = "path::to::check")]` to validate new values
+/// before they're set. The validator takes `&FieldType` and returns `Result<(), E>` where `E: Display`;
+/// an error is raised as `ValueError`. Skip a field with `#[getset(skip)]`.
+///
+/// Apply this to the struct definition. The methods go in a new `#[pymethods]` block, so
+/// if the struct has other `#[pymethods]` blocks you need pyo3's `multiple-pymethods` feature.
+///
+/// # Example
+///
+/// ```ignore
+/// #[getters_setters]
+/// #[pyclass]
+/// pub struct Config {
+///     #[getset(validate_with = "check_percentile")]
+///     percentile: u16,
+///     #[getset(skip)]
+///     inner: SomethingElse,
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn getters_setters(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |_, item| {
+        let mut ast = parse_target!(item as syn::ItemStruct, "getters_setters");
+        let fields = match &mut ast.fields {
+            syn::Fields::Named(fields) => fields,
+            _ => {
+                return errors::spanned(
+                    ErrorKind::WrongItem,
+                    &ast.ident,
+                    "expected a struct with named fields",
+                )
+                .to_compile_error()
+                .into()
+            }
+        };
+        let mut methods = Vec::new();
+        for field in fields.named.iter_mut() {
+            let (skip, validator) =
+                match field.attrs.iter().position(|a| a.path.is_ident("getset")) {
+                    Some(idx) => {
+                        let parsed = field.attrs.remove(idx).parse_args::<MacroArgs>().and_then(
+                            |mut args| {
+                                let skip = args.flag("skip")?;
+                                let validator = args
+                                    .lit_str("validate_with")?
+                                    .map(|lit| lit.parse::<syn::Path>())
+                                    .transpose()?;
+                                args.finish()?;
+                                Ok((skip, validator))
+                            },
+                        );
+                        match parsed {
+                            Ok(parsed) => parsed,
+                            Err(e) => return e.to_compile_error().into(),
+                        }
+                    }
+                    None => (false, None),
+                };
+            if skip {
+                continue;
+            }
+            let name = field.ident.as_ref().unwrap();
+            let setter_name = format_ident!("set_{}", name);
+            let field_type = &field.ty;
+            let docs = field.attrs.iter().filter(|a| a.path.is_ident("doc"));
+            let validation = validator.map(|validator| {
+                quote! {
+                    #validator(&value).map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+                }
+            });
+            methods.push(quote! {
+                #(#docs)*
+                #[getter]
+                pub fn #name(&self) -> #field_type {
+                    self.#name.clone()
+                }
+
+                #[setter]
+                pub fn #setter_name(&mut self, value: #field_type) -> pyo3::prelude::PyResult<()> {
+                    #validation
+                    self.#name = value;
+                    Ok(())
+                }
+            });
+        }
+        let name = &ast.ident;
+        let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+        let expanded = quote! {
+            #ast
+
+            #[pyo3::pymethods]
+            impl #impl_generics #name #ty_generics #where_clause {
+                #(#methods)*
+            }
+        };
+        TokenStream::from(expanded)
+    })
+}
+
+struct ReplaceArgs {
+    with_methods: bool,
+}
+
+impl Parse for ReplaceArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            with_methods: args.flag("with_methods")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Add a dataclass-style `replace(**kwargs)` method that returns a modified copy.
+///
+/// Every named field can be passed as a keyword argument. Unknown keywords raise `TypeError`.
+/// With `#[replace_methods(with_methods)]` a `with_<field>(value)` method is also added per field.
+/// The struct must be `Clone` and every field type must implement `FromPyObject`.
+///
+/// Apply this to the struct definition. The methods go in a new `#[pymethods]` block, so
+/// if the struct has other `#[pymethods]` blocks you need pyo3's `multiple-pymethods` feature.
+#[proc_macro_attribute]
+pub fn replace_methods(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let ReplaceArgs { with_methods } = parse_macro_input!(args as ReplaceArgs);
+        let ast = parse_target!(item as syn::ItemStruct, "replace_methods");
+        let fields = match &ast.fields {
+            syn::Fields::Named(fields) => fields,
+            _ => {
+                return errors::spanned(
+                    ErrorKind::WrongItem,
+                    &ast.ident,
+                    "expected a struct with named fields",
+                )
+                .to_compile_error()
+                .into()
+            }
+        };
+        let names: Vec<&Ident> = fields
+            .named
+            .iter()
+            .map(|f| f.ident.as_ref().unwrap())
+            .collect();
+        let name_strs = names.iter().map(|n| n.to_string());
+        let with_fns = if with_methods {
+            let with_names = names.iter().map(|n| format_ident!("with_{}", n));
+            let types = fields.named.iter().map(|f| &f.ty);
+            let docs = names
+                .iter()
+                .map(|n| format!(" Return a copy with ``{}`` replaced.", n));
+            quote! {
+                #(
+                    #[doc = #docs]
+                    pub fn #with_names(&self, value: #types) -> Self {
+                        let mut new = self.clone();
+                        new.#names = value;
+                        new
+                    }
+                )*
+            }
+        } else {
+            quote! {}
+        };
+        let name = &ast.ident;
+        let kwargs_type = bound::arg(quote! { PyDict });
+        let methods_in_scope = bound::methods_in_scope();
+        let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+        let expanded = quote! {
+            #ast
+
+            #[pyo3::pymethods]
+            impl #impl_generics #name #ty_generics #where_clause {
+                /// Return a copy with the given fields replaced.
+                ///
+                /// Args:
+                ///     **kwargs: The fields to replace and their new values.
+                ///
+                /// Returns:
+                ///     A modified copy of this object.
+                ///
+                #[pyo3(signature = (**kwargs))]
+                pub fn replace(&self, kwargs: Option<#kwargs_type>) -> pyo3::prelude::PyResult<Self> {
+                    #methods_in_scope
+                    let mut new = self.clone();
+                    if let Some(kwargs) = kwargs {
+                        for (key, value) in kwargs.iter() {
+                            let key: String = key.extract()?;
+                            match key.as_str() {
+                                #(#name_strs => new.#names = value.extract()?,)*
+                                _ => {
+                                    return Err(pyo3::exceptions::PyTypeError::new_err(format!(
+                                        "replace() got an unexpected keyword argument '{}'",
+                                        key
+                                    )))
+                                }
+                            }
+                        }
+                    }
+                    Ok(new)
+                }
+
+                #with_fns
+            }
+        };
+        TokenStream::from(expanded)
+    })
+}
+
+struct RegisterClassArgs {
+    submodule: syn::LitStr,
+}
+
+impl Parse for RegisterClassArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            submodule: args.required_lit_str("submodule")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Record the class in the class registry so `register_all` adds it to the given submodule.
+///
+/// Use this on impl blocks of classes that don't use `#[common_methods(register = "...")]`.
+/// An empty submodule means the top-level module. See `class_registry!`.
+#[proc_macro_attribute]
+pub fn register_class(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let RegisterClassArgs { submodule } = parse_macro_input!(args as RegisterClassArgs);
+        let ast = parse_target!(item as ItemImpl, "register_class");
+        if let Err(e) = reject_generics(&ast, "register_class") {
+            return e.to_compile_error().into();
+        }
+        let mut new_stream = ast.to_token_stream();
+        new_stream.extend(registry::registration(&ast.self_ty, &submodule));
+        TokenStream::from(new_stream)
+    })
+}
+
+/// Define the class registry used by `register_class` and `#[common_methods(register = "...")]`.
+///
+/// Call this once at the crate root. It defines a `RegisteredClass` type and a
+/// `register_all(py, m)` function that adds every registered class to its submodule,
+/// creating the submodules and inserting them into `sys.modules`.
+///
+/// Only those two record classes; the other macros don't, so a class needs one of them
+/// to be added by `register_all`. They refer to the registry as `crate::RegisteredClass`,
+/// so when it's defined in another crate pass them `crate = "..."`.
+/// The crate must depend on `inventory`. With the `bound` feature `register_all`
+/// takes a `&Bound<PyModule>`.
+///
+/// # Example
+///
+/// ```ignore
+/// solders_macros::class_registry!();
+///
+/// #[pymodule]
+/// fn solders(py: Python, m: &PyModule) -> PyResult<()> {
+///     register_all(py, m)
+/// }
+/// ```
+#[proc_macro]
+pub fn class_registry(input: TokenStream) -> TokenStream {
+    if !input.is_empty() {
+        return errors::at(
+            ErrorKind::UnrecognizedArgument,
+            Span::call_site(),
+            "`class_registry!` takes no arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+    TokenStream::from(registry::definition())
+}
+
+/// One entry of `#[pymodule_classes(...)]`: a class, or a submodule and its classes.
+enum ModuleClassesEntry {
+    Class(Box<Type>),
+    Submodule(Ident, Vec<Type>),
+}
+
+impl Parse for ModuleClassesEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::Ident) && input.peek2(syn::token::Paren) {
+            let name = input.parse()?;
+            let content;
+            syn::parenthesized!(content in input);
+            let classes =
+                syn::punctuated::Punctuated::<Type, syn::Token![,]>::parse_terminated(&content)?;
+            Ok(Self::Submodule(name, classes.into_iter().collect()))
+        } else {
+            Ok(Self::Class(input.parse()?))
+        }
+    }
+}
+
+/// Whether `ty` is `PyModule` or `Bound<'_, PyModule>`.
+fn is_py_module(ty: &Type) -> bool {
+    let last = match ty {
+        Type::Path(p) => p.path.segments.last().unwrap(),
+        _ => return false,
+    };
+    if last.ident == "PyModule" {
+        return true;
+    }
+    match &last.arguments {
+        syn::PathArguments::AngleBracketed(args) if last.ident == "Bound" => {
+            args.args.iter().any(|arg| match arg {
+                syn::GenericArgument::Type(ty) => is_py_module(ty),
+                _ => false,
+            })
+        }
+        _ => false,
+    }
+}
+
+/// The `&PyModule` (or `&Bound<'_, PyModule>`) argument of a `#[pymodule]` function.
+fn pymodule_arg(sig: &syn::Signature) -> syn::Result<Ident> {
+    for input in &sig.inputs {
+        if let syn::FnArg::Typed(pat_type) = input {
+            let is_module = match &*pat_type.ty {
+                Type::Reference(r) => is_py_module(&r.elem),
+                _ => false,
+            };
+            if let (true, syn::Pat::Ident(pat)) = (is_module, &*pat_type.pat) {
+                return Ok(pat.ident.clone());
+            }
+        }
+    }
+    Err(errors::spanned(
+        ErrorKind::WrongItem,
+        sig,
+        "expected a `#[pymodule]` function with a `&PyModule` or `&Bound<PyModule>` argument",
+    ))
+}
+
+/// Add classes and submodules to a `#[pymodule]` function.
+///
+/// Bare types are added to the module itself. `name(A, B)` creates a submodule called `name`
+/// holding `A` and `B`, adds it to the module and inserts it into `sys.modules` as
+/// `<module>.name` so it can be imported directly.
+/// The calls are inserted at the start of the function body.
+///
+/// # Example
+///
+/// ```ignore
+/// #[pymodule]
+/// #[pymodule_classes(Pubkey, Keypair, transaction(Transaction, Message))]
+/// fn solders(_py: Python, m: &PyModule) -> PyResult<()> {
+///     Ok(())
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn pymodule_classes(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let parser =
+            syn::punctuated::Punctuated::<ModuleClassesEntry, syn::Token![,]>::parse_terminated;
+        let entries = parse_macro_input!(args with parser);
+        let mut ast = parse_target!(item as syn::ItemFn, "pymodule_classes");
+        let m = match pymodule_arg(&ast.sig) {
+            Ok(m) => m,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        let mut top_level = Vec::new();
+        let mut submodules = Vec::new();
+        for entry in entries {
+            match entry {
+                ModuleClassesEntry::Class(ty) => top_level.push(*ty),
+                ModuleClassesEntry::Submodule(name, classes) => {
+                    submodules.push(registry::add_classes(&m, Some(&name), &classes))
+                }
+            }
+        }
+        let top_level = registry::add_classes(&m, None, &top_level);
+        let stmts = quote! {
+            #top_level
+            #(#submodules)*
+        };
+        let block: syn::Block = syn::parse_quote!({ #stmts });
+        let body = std::mem::take(&mut ast.block.stmts);
+        ast.block.stmts = block.stmts;
+        ast.block.stmts.extend(body);
+        TokenStream::from(ast.to_token_stream())
+    })
+}
+
+/// Generate a `#[pyfunction]` wrapper for a static method.
+fn pyfunction_wrapper(
+    self_ty: &Type,
+    method: &syn::ImplItemMethod,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let sig = &method.sig;
+    let name = &sig.ident;
+    if let Some(receiver) = sig.receiver() {
+        return Err(errors::spanned(
+            ErrorKind::InvalidArgument,
+            receiver,
+            format!(
+                "`{}` takes `self`; only static methods can be exported",
+                name
+            ),
+        ));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(errors::spanned(
+            ErrorKind::Generic,
+            &sig.generics,
+            "generic methods can't be exported as Python functions",
+        ));
+    }
+    let mut arg_names = Vec::new();
+    for input in &sig.inputs {
+        match input {
+            syn::FnArg::Typed(pat_type) => match &*pat_type.pat {
+                syn::Pat::Ident(pat) => arg_names.push(pat.ident.clone()),
+                other => {
+                    return Err(errors::spanned(
+                        ErrorKind::InvalidArgument,
+                        other,
+                        "expected a plain argument name",
+                    ))
+                }
+            },
+            syn::FnArg::Receiver(_) => unreachable!(),
+        }
+    }
+    let attrs = method
+        .attrs
+        .iter()
+        .filter(|a| a.path.is_ident("doc") || a.path.is_ident("pyo3"));
+    let inputs = &sig.inputs;
+    let output = &sig.output;
+    Ok(quote! {
+        #(#attrs)*
+        #[pyo3::pyfunction]
+        pub fn #name(#inputs) #output {
+            <#self_ty>::#name(#(#arg_names),*)
+        }
+    })
+}
+
+/// Export static methods as module-level Python functions.
+///
+/// For each listed method this adds a `#[pyfunction]` of the same name next to the impl block,
+/// delegating to the method and keeping its docs and `#[pyo3(...)]` attributes.
+/// It also adds a `register_functions(m)` associated function to the type that adds the
+/// wrappers to a module with `wrap_pyfunction!`.
+///
+/// # Example
+///
+/// ```ignore
+/// #[export_functions(create_program_address)]
+/// #[pymethods]
+/// impl Pubkey {
+///     #[staticmethod]
+///     pub fn create_program_address(seeds: Vec<&[u8]>, program_id: &Pubkey) -> Pubkey {
+///         ...
+///     }
+/// }
+///
+/// #[pymodule]
+/// fn solders(_py: Python, m: &PyModule) -> PyResult<()> {
+///     Pubkey::register_functions(m)
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn export_functions(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let parser = syn::punctuated::Punctuated::<Ident, syn::Token![,]>::parse_terminated;
+        let names = parse_macro_input!(args with parser);
+        let ast = parse_target!(item as ItemImpl, "export_functions");
+        if let Err(e) = reject_generics(&ast, "export_functions") {
+            return e.to_compile_error().into();
+        }
+        let self_ty = &ast.self_ty;
+        let mut wrappers = Vec::new();
+        for name in &names {
+            let method = ast.items.iter().find_map(|item| match item {
+                ImplItem::Method(m) if m.sig.ident == *name => Some(m),
+                _ => None,
+            });
+            let wrapper = match method {
+                Some(method) => pyfunction_wrapper(self_ty, method),
+                None => Err(errors::spanned(
+                    ErrorKind::InvalidArgument,
+                    name,
+                    format!("no method named `{}` in this impl block", name),
+                )),
+            };
+            match wrapper {
+                Ok(wrapper) => wrappers.push(wrapper),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        }
+        let names = names.iter();
+        let m = bound::arg(quote! { PyModule });
+        let methods_in_scope = bound::methods_in_scope();
+        let generated = quote! {
+            #(#wrappers)*
+
+            impl #self_ty {
+                /// Add the functions exported by `export_functions` to a Python module.
+                pub fn register_functions(m: #m) -> pyo3::prelude::PyResult<()> {
+                    #methods_in_scope
+                    #(m.add_function(pyo3::wrap_pyfunction!(#names, m)?)?;)*
+                    Ok(())
+                }
+            }
+        };
+        let mut new_stream = ast.to_token_stream();
+        new_stream.extend(generated);
+        TokenStream::from(new_stream)
+    })
+}
+
+struct PyiStubArgs {
+    module: syn::LitStr,
+}
+
+impl Parse for PyiStubArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            module: args.required_lit_str("module")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Record a `.pyi` stub for the class. Only does anything with the `stubs` feature enabled.
+///
+/// The stub has a signature with Python type annotations and the docstring for every method
+/// in the impl block, including methods added by the other macros in this crate.
+/// So that it sees those methods, put it last, just above `#[pymethods]`.
+/// The stub is written to `$OUT_DIR/solders_stubs/<module>/<Class>.<id>.pyi`, one file per
+/// impl block, so the calling crate needs a build script (an empty one will do) for `OUT_DIR`
+/// to be set. Use `stub_writer!` to assemble the stubs into one file per module; a class
+/// with several annotated impl blocks gets a single merged class body.
+///
+/// # Example
+///
+/// ```ignore
+/// #[common_methods]
+/// #[pyi_stub(module = "transaction")]
+/// #[pymethods]
+/// impl Transaction {
+///     ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn pyi_stub(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let PyiStubArgs { module } = parse_macro_input!(args as PyiStubArgs);
+        let ast = parse_target!(item as ItemImpl, "pyi_stub");
+        if cfg!(feature = "stubs") {
+            let written = self_type_ident(&ast).and_then(|class| {
+                stubs::write_class_stub(&module.value(), &class.to_string(), &ast)
+            });
+            if let Err(e) = written {
+                return e.to_compile_error().into();
+            }
+        }
+        TokenStream::from(ast.to_token_stream())
+    })
+}
+
+/// Define `write_stubs(dest: &Path) -> std::io::Result<()>`, which joins the stubs recorded
+/// by `#[pyi_stub]` into `<dest>/<module>.pyi` files.
+///
+/// Call it from a test or binary in the crate that uses `#[pyi_stub]`, after building with
+/// the `stubs` feature.
+///
+/// # Example
+///
+/// ```ignore
+/// solders_macros::stub_writer!();
+///
+/// #[test]
+/// fn generate_stubs() {
+///     write_stubs(std::path::Path::new("python/solders")).unwrap();
+/// }
+/// ```
+#[proc_macro]
+pub fn stub_writer(input: TokenStream) -> TokenStream {
+    if !input.is_empty() {
+        return errors::at(
+            ErrorKind::UnrecognizedArgument,
+            Span::call_site(),
+            "`stub_writer!` takes no arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+    TokenStream::from(stubs::writer())
+}
+
+struct ArgsDocstringArgs {
+    args: Option<Vec<Ident>>,
+}
+
+impl Parse for ArgsDocstringArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            args: args.ident_list("args")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Split a `Type: description` field doc, as used for getters, into its type and description.
+fn split_doc_type(doc: &str) -> Option<(&str, &str)> {
+    let (ty, description) = doc.split_once(": ")?;
+    let mut depth = 0;
+    for ch in ty.chars() {
+        match ch {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            c if c.is_whitespace() && depth == 0 => return None,
+            _ => {}
+        }
+    }
+    Some((ty, description))
+}
+
+/// Append a Google-style `Args:` section to the struct's docstring, built from its fields.
+///
+/// Each argument's description is its field's doc comment. A leading `Type:` in the doc comment,
+/// as used for getter docs, becomes the argument's type; otherwise the type is derived from
+/// the field's Rust type.
+///
+/// By default every field is listed in declaration order. If `#[new]` takes a subset of the
+/// fields or a different order, list its parameters with `#[args_docstring(args(a, b))]`.
+///
+/// Apply this above `#[pyclass]` so the class docstring includes the section.
+///
+/// # Example
+///
+/// ```rust
+/// use solders_macros::args_docstring;
+///
+/// /// Describes a single account read or written by a program.
+/// #[args_docstring]
+/// pub struct AccountMeta {
+///     /// Pubkey: The account's public key.
+///     pubkey: [u8; 32],
+///     /// Whether the account is a signer.
+///     is_signer: bool,
+/// }
+/// ```
+///
+/// The docstring becomes:
+///
+/// ```text
+/// Describes a single account read or written by a program.
+///
+/// Args:
+///     pubkey (Pubkey): The account's public key.
+///     is_signer (bool): Whether the account is a signer.
+/// ```
+#[proc_macro_attribute]
+pub fn args_docstring(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let ArgsDocstringArgs { args } = parse_macro_input!(args as ArgsDocstringArgs);
+        let mut ast = parse_target!(item as syn::ItemStruct, "args_docstring");
+        let fields = match &ast.fields {
+            syn::Fields::Named(fields) => &fields.named,
+            _ => {
+                return errors::spanned(
+                    ErrorKind::WrongItem,
+                    &ast.ident,
+                    "expected a struct with named fields",
+                )
+                .to_compile_error()
+                .into()
+            }
+        };
+        let names =
+            args.unwrap_or_else(|| fields.iter().map(|f| f.ident.clone().unwrap()).collect());
+        let class = ast.ident.to_string();
+        // Leading spaces match what `///` comments produce.
+        let mut lines = vec![String::new(), " Args:".to_owned()];
+        for name in &names {
+            let field = match fields.iter().find(|f| f.ident.as_ref() == Some(name)) {
+                Some(field) => field,
+                None => {
+                    return errors::spanned(
+                        ErrorKind::InvalidArgument,
+                        name,
+                        format!("no field named `{}`", name),
+                    )
+                    .to_compile_error()
+                    .into()
+                }
+            };
+            let doc = stubs::docstring(&field.attrs).replace('\n', " ");
+            let (ty, description) = match split_doc_type(&doc) {
+                Some((ty, description)) => (ty.to_owned(), description.to_owned()),
+                None => (stubs::py_type(&field.ty, &class), doc),
+            };
+            let line = if description.is_empty() {
+                format!("     {} ({})", name, ty)
+            } else {
+                format!("     {} ({}): {}", name, ty, description)
+            };
+            lines.push(line);
+        }
+        ast.attrs
+            .extend(lines.iter().map(|line| syn::parse_quote!(#[doc = #line])));
+        TokenStream::from(ast.to_token_stream())
+    })
+}
+
+struct PyclassBoilerplateArgs {
+    module: Option<syn::LitStr>,
+    frozen: bool,
+    eq: bool,
+    get_all: bool,
+}
+
+impl Parse for PyclassBoilerplateArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args: MacroArgs = input.parse()?;
+        let parsed = Self {
+            module: args.lit_str("module")?,
+            frozen: args.flag("frozen")?,
+            eq: args.flag("eq")?,
+            get_all: args.flag("get_all")?,
+        };
+        args.finish()?;
+        Ok(parsed)
+    }
+}
+
+/// Declare a solders class with the project's standard `#[pyclass]` options and derives.
+///
+/// This adds `#[pyclass(module = "solders.<module>", subclass)]` and derives whichever of
+/// `Clone`, `Debug` and `PartialEq` the struct's own `#[derive]`s don't already list. The
+/// `module` argument is the submodule name; without it the class lives in `solders`. The
+/// `frozen`, `eq` and `get_all` flags are passed on to `#[pyclass]` and need a pyo3 version
+/// that supports them: `frozen` and `get_all` need 0.18, which also allows `frozen` classes to
+/// be `subclass`ed, and `eq` needs 0.22.
+///
+/// # Example
+///
+/// ```ignore
+/// #[pyclass_boilerplate(module = "instruction", get_all)]
+/// pub struct AccountMeta {
+///     pubkey: Pubkey,
+///     is_signer: bool,
+///     is_writable: bool,
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn pyclass_boilerplate(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        let PyclassBoilerplateArgs {
+            module,
+            frozen,
+            eq,
+            get_all,
+        } = parse_macro_input!(args as PyclassBoilerplateArgs);
+        let ast = parse_target!(item as syn::ItemStruct, "pyclass_boilerplate");
+        let module_path = match module {
+            Some(module) => syn::LitStr::new(&format!("solders.{}", module.value()), module.span()),
+            None => syn::LitStr::new("solders", Span::call_site()),
+        };
+        let mut options = vec![quote! { module = #module_path }, quote! { subclass }];
+        if frozen {
+            options.push(quote! { frozen });
+        }
+        if eq {
+            options.push(quote! { eq });
+        }
+        if get_all {
+            options.push(quote! { get_all });
+        }
+        let derived = match derived_traits(&ast.attrs) {
+            Ok(derived) => derived,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        let derives: Vec<Ident> = ["Clone", "Debug", "PartialEq"]
+            .iter()
+            .filter(|name| !derived.iter().any(|d| d == *name))
+            .map(|name| format_ident!("{}", name))
+            .collect();
+        let derive_attr = if derives.is_empty() {
+            quote! {}
+        } else {
+            quote! { #[derive(#(#derives),*)] }
+        };
+        let expanded = quote! {
+            #[pyo3::pyclass(#(#options),*)]
+            #derive_attr
+            #ast
+        };
+        TokenStream::from(expanded)
+    })
+}
+
+/// The last path segments of the traits in the `#[derive(...)]` attributes, e.g. `Clone` for
+/// `std::clone::Clone`.
+fn derived_traits(attrs: &[syn::Attribute]) -> syn::Result<Vec<Ident>> {
+    let mut derived = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("derive")) {
+        let paths = attr.parse_args_with(
+            syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated,
+        )?;
+        derived.extend(
+            paths
+                .iter()
+                .filter_map(|path| path.segments.last().map(|seg| seg.ident.clone())),
+        );
+    }
+    Ok(derived)
+}
+
+/// Use in place of `#[pymethods]` to register the methods added by this crate's macros
+/// regardless of attribute order.
+///
+/// A plain `#[pymethods]` must come after the macros that add methods: if it's listed first it
+/// expands first and never sees the added methods. This attribute instead moves a
+/// `#[pyo3::pymethods]` to the end of the impl's attributes, so it always runs last.
+///
+/// # Example
+///
+/// ```ignore
+/// #[solders_pymethods]
+/// #[common_methods]
+/// #[pyhash]
+/// impl Pubkey {
+///     ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn solders_pymethods(args: TokenStream, item: TokenStream) -> TokenStream {
+    paths::expand(args, item, |args, item| {
+        if !args.is_empty() {
+            return errors::at(
+                ErrorKind::UnrecognizedArgument,
+                Span::call_site(),
+                "`solders_pymethods` takes no arguments",
+            )
+            .to_compile_error()
+            .into();
+        }
+        let mut ast = parse_target!(item as ItemImpl, "solders_pymethods");
+        if let Some(attr) = ast.attrs.iter().find(|a| is_pymethods_attr(a)) {
+            return errors::spanned(
+                ErrorKind::InvalidArgument,
+                attr,
+                "`solders_pymethods` replaces `#[pymethods]`",
+            )
+            .to_compile_error()
+            .into();
+        }
+        ast.attrs.push(syn::parse_quote!(#[pyo3::pymethods]));
+        TokenStream::from(ast.to_token_stream())
+    })
 }
@@ -0,0 +1,153 @@
+//! JSON descriptions of generated methods for the `metadata` feature.
+//!
+//! Each impl-block macro records the methods it added in
+//! `$OUT_DIR/solders_metadata/<Class>.<macro>.<id>.json`, where the id is from
+//! `out_dir::fragment_id`, so classes of the same name in different modules don't overwrite
+//! each other. The JSON also has the impl block's file and line, which the compiler only
+//! reports from Rust 1.88; older versions give `"<token stream>"` and `0`. For example:
+//!
+//! ```json
+//! {"class": "Pubkey", "macro": "common_methods", "file": "src/pubkey.rs", "line": 12, "methods": [
+//!   {"name": "from_json", "kind": "staticmethod", "doc": "Build from a JSON string.",
+//!    "params": [{"name": "raw", "type": "&str", "python_type": "str"}],
+//!    "returns": {"type": "PyResult<Self>", "python_type": "Pubkey"}}
+//! ]}
+//! ```
+use proc_macro2::Span;
+use quote::ToTokens;
+use std::path::Path;
+use syn::spanned::Spanned;
+use syn::{FnArg, ImplItem, ImplItemMethod, ItemImpl, Type};
+
+use crate::errors::{self, ErrorKind};
+use crate::stubs::py_type;
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn type_json(ty: &Type, class: &str) -> String {
+    format!(
+        "{{\"type\": {}, \"python_type\": {}}}",
+        json_string(&ty.to_token_stream().to_string()),
+        json_string(&py_type(ty, class))
+    )
+}
+
+fn method_kind(method: &ImplItemMethod) -> &'static str {
+    let has = |name: &str| method.attrs.iter().any(|a| a.path.is_ident(name));
+    if has("new") {
+        "constructor"
+    } else if has("staticmethod") {
+        "staticmethod"
+    } else if has("classmethod") {
+        "classmethod"
+    } else if has("getter") {
+        "getter"
+    } else if has("setter") {
+        "setter"
+    } else {
+        "method"
+    }
+}
+
+fn method_json(method: &ImplItemMethod, class: &str) -> String {
+    let doc: Vec<String> = method
+        .attrs
+        .iter()
+        .filter(|a| a.path.is_ident("doc"))
+        .filter_map(|a| match a.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(s),
+                ..
+            })) => Some(s.value()),
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(str::to_owned).unwrap_or(line))
+        .collect();
+    let params: Vec<String> = method
+        .sig
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Typed(pat_type) if !crate::is_python_token(&pat_type.ty) => Some(format!(
+                "{{\"name\": {}, {}",
+                json_string(&pat_type.pat.to_token_stream().to_string()),
+                &type_json(&pat_type.ty, class)[1..]
+            )),
+            _ => None,
+        })
+        .collect();
+    let returns = match &method.sig.output {
+        syn::ReturnType::Default => "null".to_owned(),
+        syn::ReturnType::Type(_, ty) => type_json(ty, class),
+    };
+    format!(
+        "{{\"name\": {}, \"kind\": {}, \"doc\": {}, \"params\": [{}], \"returns\": {}}}",
+        json_string(&method.sig.ident.to_string()),
+        json_string(method_kind(method)),
+        json_string(doc.join("\n").trim()),
+        params.join(", "),
+        returns
+    )
+}
+
+/// Record the methods `macro_name` added to `ast`.
+///
+/// The added methods are the `ImplItem::Verbatim` items: methods written by hand, or added by
+/// a macro that ran earlier, have already been parsed into `ImplItem::Method`.
+/// Does nothing if `OUT_DIR` isn't set, i.e. the calling crate has no build script.
+/// Metadata from earlier builds is removed as described in `out_dir`.
+pub(crate) fn record(macro_name: &str, ast: &ItemImpl) -> syn::Result<()> {
+    if std::env::var_os("OUT_DIR").is_none() {
+        return Ok(());
+    }
+    let class = crate::self_type_ident(ast)?.to_string();
+    let methods: Vec<String> = ast
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Verbatim(tokens) => syn::parse2::<ImplItemMethod>(tokens.clone()).ok(),
+            _ => None,
+        })
+        .map(|method| method_json(&method, &class))
+        .collect();
+    let span = ast.self_ty.span();
+    let (file, start) = (span.file(), span.start());
+    let json = format!(
+        "{{\"class\": {}, \"macro\": {}, \"file\": {}, \"line\": {}, \"methods\": [\n  {}\n]}}\n",
+        json_string(&class),
+        json_string(macro_name),
+        json_string(&file),
+        start.line,
+        methods.join(",\n  ")
+    );
+    let name = format!(
+        "{}.{}.{}.json",
+        class,
+        macro_name,
+        crate::out_dir::fragment_id(macro_name, ast)
+    );
+    crate::out_dir::write("solders_metadata", Path::new(&name), &json)
+        .unwrap_or(Ok(()))
+        .map_err(|e| {
+            errors::at(
+                ErrorKind::Io,
+                Span::call_site(),
+                format!("failed to write metadata for `{}`: {}", class, e),
+            )
+        })
+}
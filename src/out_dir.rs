@@ -0,0 +1,125 @@
+//! The directories that the `stubs` and `metadata` features write to in the calling crate's
+//! `OUT_DIR`.
+//!
+//! The lib, bin and test targets of a package share one `OUT_DIR` and can compile at the same
+//! time, so these directories are never emptied. Instead each file is written to a temporary
+//! file and renamed into place, and each target lists the files it wrote in its own manifest,
+//! `.<target>.manifest`. The first write from a compilation removes the files the target's
+//! previous manifest lists that no other target's manifest does, which drops the files of
+//! items removed or renamed since the last build. A build in which no macro writes anything
+//! leaves the directory as is.
+//!
+//! Files starting with `.` are this bookkeeping; readers of the directories should skip them.
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// The directories already pruned by this compiler process, and when the first was.
+static PRUNED: Mutex<(Vec<&'static str>, Option<SystemTime>)> = Mutex::new((Vec::new(), None));
+
+/// Numbers the temporary files and fragment names of this compiler process.
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A file name stem for a fragment that `macro_name` records for `item`.
+///
+/// It hashes the macro name, the item's tokens and a per-process counter, so two impl blocks
+/// for the same class get different files even if they're identical. Unlike span locations,
+/// these are available on every Rust version.
+pub(crate) fn fragment_id(macro_name: &str, item: &impl quote::ToTokens) -> String {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    (
+        macro_name,
+        COUNTER.fetch_add(1, Ordering::Relaxed),
+        item.to_token_stream().to_string(),
+    )
+        .hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Write `contents` to `$OUT_DIR/<name>/<file>`, creating directories as needed.
+/// `None` if `OUT_DIR` isn't set, i.e. the calling crate has no build script.
+pub(crate) fn write(
+    name: &'static str,
+    file: &Path,
+    contents: &str,
+) -> Option<std::io::Result<()>> {
+    let dir = PathBuf::from(std::env::var_os("OUT_DIR")?).join(name);
+    Some(write_in(&dir, name, file, contents))
+}
+
+fn write_in(dir: &Path, name: &'static str, file: &Path, contents: &str) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let manifest = dir.join(format!(".{}.manifest", target()));
+    {
+        let mut pruned = PRUNED.lock().unwrap_or_else(|e| e.into_inner());
+        let started = *pruned.1.get_or_insert_with(SystemTime::now);
+        if !pruned.0.contains(&name) {
+            prune(dir, &manifest, started)?;
+            pruned.0.push(name);
+        }
+    }
+    let path = dir.join(file);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = dir.join(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(&tmp, &path)?;
+    let mut manifest = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(manifest)?;
+    manifest.write_all(format!("{}\n", file.display()).as_bytes())
+}
+
+/// The target being compiled, e.g. `solders` or `solders-test`, naming its manifest.
+fn target() -> String {
+    let mut target = std::env::var("CARGO_CRATE_NAME").unwrap_or_else(|_| "crate".to_owned());
+    if std::env::var_os("CARGO_BIN_NAME").is_some() {
+        target.push_str("-bin");
+    }
+    // The lib and its unit tests have the same crate name; only rustc's arguments differ.
+    if std::env::args().any(|arg| arg == "--test") {
+        target.push_str("-test");
+    }
+    target
+}
+
+/// Remove the files listed in `manifest` that no other manifest in `dir` lists, then empty it.
+///
+/// Files changed since `started` are kept, since another target compiling at the same time
+/// has just written them.
+fn prune(dir: &Path, manifest: &Path, started: SystemTime) -> std::io::Result<()> {
+    let previous = match std::fs::read_to_string(manifest) {
+        Ok(previous) => previous,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let mut listed = HashSet::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_manifest = path.extension().is_some_and(|ext| ext == "manifest");
+        if is_manifest && path != manifest {
+            listed.extend(std::fs::read_to_string(&path)?.lines().map(str::to_owned));
+        }
+    }
+    for file in previous.lines().filter(|file| !listed.contains(*file)) {
+        let path = dir.join(file);
+        let changed = std::fs::metadata(&path).and_then(|m| m.modified());
+        if matches!(changed, Ok(changed) if changed < started) {
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+    }
+    std::fs::write(manifest, "")
+}
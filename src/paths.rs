@@ -0,0 +1,126 @@
+//! Crate paths in generated code, overridable with the `crate` and `pyo3` arguments
+//! that every attribute macro accepts, along with `cfg` (see the `cfg` module).
+//!
+//! Generated code refers to solders items (like `Signer`) through the `__solders_crate`
+//! placeholder, and to pyo3 as `pyo3`. After expansion both are replaced by the configured
+//! paths, which default to `crate` and `pyo3`.
+//!
+//! The rewrite can't tell generated tokens from the user's, so it also covers the item the
+//! macro is applied to.
+use crate::cfg;
+use crate::errors::{self, ErrorKind};
+use proc_macro2::{Group, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{LitStr, Path};
+
+const PLACEHOLDER: &str = "__solders_crate";
+
+#[derive(Default)]
+struct PathArgs {
+    krate: Option<Path>,
+    pyo3: Option<Path>,
+    cfg: Option<TokenStream>,
+}
+
+/// Take `crate = "..."`, `pyo3 = "..."` and `cfg = ...` out of a macro's arguments,
+/// returning the rest.
+fn split_args(args: TokenStream) -> syn::Result<(PathArgs, TokenStream)> {
+    let mut segments: Vec<Vec<TokenTree>> = vec![Vec::new()];
+    for tree in args {
+        match &tree {
+            TokenTree::Punct(p) if p.as_char() == ',' => segments.push(Vec::new()),
+            _ => segments.last_mut().unwrap().push(tree),
+        }
+    }
+    let mut paths = PathArgs::default();
+    let mut rest = Vec::new();
+    for segment in segments {
+        let slot = match segment.as_slice() {
+            [TokenTree::Ident(name), TokenTree::Punct(eq), predicate @ ..]
+                if eq.as_char() == '=' && name == "cfg" =>
+            {
+                let predicate: TokenStream = predicate.iter().cloned().collect();
+                if syn::parse2::<syn::NestedMeta>(predicate.clone()).is_err() {
+                    return Err(errors::at(
+                        ErrorKind::InvalidArgument,
+                        name.span(),
+                        "expected `cfg = <predicate>`, e.g. `cfg = feature = \"ring\"`",
+                    ));
+                }
+                paths.cfg = Some(predicate);
+                continue;
+            }
+            [TokenTree::Ident(name), TokenTree::Punct(eq), TokenTree::Literal(_)]
+                if eq.as_char() == '=' && (name == "crate" || name == "pyo3") =>
+            {
+                if name == "crate" {
+                    &mut paths.krate
+                } else {
+                    &mut paths.pyo3
+                }
+            }
+            [] => continue,
+            _ => {
+                rest.push(segment.into_iter().collect::<TokenStream>());
+                continue;
+            }
+        };
+        let lit: LitStr = syn::parse2(segment[2].to_token_stream())?;
+        *slot = Some(lit.parse()?);
+    }
+    Ok((paths, quote! { #(#rest),* }))
+}
+
+/// Replace the placeholder, and `pyo3` at the start of paths if overridden.
+///
+/// This runs over user tokens too, so a `pyo3::` path in the annotated item is rewritten as well.
+fn rewrite(tokens: TokenStream, paths: &PathArgs) -> TokenStream {
+    let trees: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut out = TokenStream::new();
+    for (i, tree) in trees.iter().enumerate() {
+        match tree {
+            TokenTree::Group(group) => {
+                let mut new_group = Group::new(group.delimiter(), rewrite(group.stream(), paths));
+                new_group.set_span(group.span());
+                out.extend(std::iter::once(TokenTree::Group(new_group)));
+            }
+            TokenTree::Ident(ident) if ident == PLACEHOLDER => match &paths.krate {
+                Some(krate) => krate.to_tokens(&mut out),
+                None => out.extend(quote! { crate }),
+            },
+            TokenTree::Ident(ident) if ident == "pyo3" && paths.pyo3.is_some() => {
+                let is_colon =
+                    |tree: &TokenTree| matches!(tree, TokenTree::Punct(p) if p.as_char() == ':');
+                // `a::pyo3::b` is left alone, but not the `:` of `x: pyo3::Type`.
+                let after_colons = i >= 2 && is_colon(&trees[i - 1]) && is_colon(&trees[i - 2]);
+                let before_colons = trees.get(i + 1).is_some_and(is_colon);
+                if before_colons && !after_colons {
+                    paths.pyo3.as_ref().unwrap().to_tokens(&mut out);
+                } else {
+                    out.extend(std::iter::once(tree.clone()));
+                }
+            }
+            _ => out.extend(std::iter::once(tree.clone())),
+        }
+    }
+    out
+}
+
+/// Run an attribute macro with the path and `cfg` arguments removed, then apply them to its output.
+pub(crate) fn expand(
+    args: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+    f: impl FnOnce(proc_macro::TokenStream, proc_macro::TokenStream) -> proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    match split_args(args.into()) {
+        Ok((paths, rest)) => {
+            let input = TokenStream::from(item.clone());
+            let mut output = TokenStream::from(f(rest.into(), item));
+            if let Some(predicate) = &paths.cfg {
+                output = cfg::apply(output, input, predicate);
+            }
+            rewrite(output, &paths).into()
+        }
+        Err(e) => e.to_compile_error().into(),
+    }
+}
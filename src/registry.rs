@@ -0,0 +1,97 @@
+//! Code generation for the `inventory`-based class registry.
+use crate::bound;
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::{LitStr, Type};
+
+/// Register `self_ty` to be added to `submodule` by the generated `register_all`.
+pub(crate) fn registration(self_ty: &Type, submodule: &LitStr) -> TokenStream {
+    let methods_in_scope = bound::methods_in_scope();
+    quote! {
+        inventory::submit! {
+            __solders_crate::RegisteredClass {
+                submodule: #submodule,
+                add: |m| {
+                    #methods_in_scope
+                    m.add_class::<#self_ty>()
+                },
+            }
+        }
+    }
+}
+
+/// The registry type and the `register_all` function, emitted once per crate.
+pub(crate) fn definition() -> TokenStream {
+    let module_arg = bound::arg(quote! { PyModule });
+    let module = bound::owned(quote! { PyModule });
+    let new_module = bound::new(quote! { PyModule });
+    let import_sys = bound::import(quote! { py }, "sys");
+    let methods_in_scope = bound::methods_in_scope();
+    quote! {
+        /// A Python class recorded by `register_class` or `#[common_methods(register = "...")]`.
+        pub struct RegisteredClass {
+            /// The submodule to add the class to. Empty means the top-level module.
+            pub submodule: &'static str,
+            /// Adds the class to a module.
+            pub add: fn(#module_arg) -> pyo3::prelude::PyResult<()>,
+        }
+
+        inventory::collect!(RegisteredClass);
+
+        /// Add every registered class to `m` or the submodule it was registered with.
+        ///
+        /// Submodules are created as needed, added to `m` and inserted into `sys.modules`
+        /// so they can be imported directly.
+        pub fn register_all<'a>(py: pyo3::prelude::Python<'a>, m: #module_arg) -> pyo3::prelude::PyResult<()> {
+            #methods_in_scope
+            let mut submodules: std::collections::BTreeMap<&'static str, #module> =
+                std::collections::BTreeMap::new();
+            for class in inventory::iter::<RegisteredClass> {
+                if class.submodule.is_empty() {
+                    (class.add)(m)?;
+                    continue;
+                }
+                if !submodules.contains_key(class.submodule) {
+                    submodules.insert(class.submodule, #new_module(py, class.submodule)?);
+                }
+                (class.add)(&submodules[class.submodule])?;
+            }
+            let sys_modules = #import_sys?.getattr("modules")?;
+            let parent_name = m.name()?;
+            for (name, submodule) in &submodules {
+                m.add_submodule(submodule)?;
+                sys_modules.set_item(format!("{}.{}", parent_name, name), submodule)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Statements adding `classes` to the module `m`, or to a new submodule named `submodule`.
+pub(crate) fn add_classes(m: &Ident, submodule: Option<&Ident>, classes: &[Type]) -> TokenStream {
+    let methods_in_scope = bound::methods_in_scope();
+    match submodule {
+        None => quote! {
+            {
+                #methods_in_scope
+                #(#m.add_class::<#classes>()?;)*
+            }
+        },
+        Some(submodule) => {
+            let name = submodule.to_string();
+            let new_module = bound::new(quote! { PyModule });
+            let import_sys = bound::import(quote! { #m.py() }, "sys");
+            quote! {
+                {
+                    #methods_in_scope
+                    let #submodule = #new_module(#m.py(), #name)?;
+                    #(#submodule.add_class::<#classes>()?;)*
+                    #m.add_submodule(&#submodule)?;
+                    #import_sys?
+                        .getattr("modules")?
+                        .set_item(format!("{}.{}", #m.name()?, #name), &#submodule)?;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,348 @@
+//! `.pyi` stub generation for the `stubs` feature.
+//!
+//! `#[pyi_stub]` renders the methods of an impl block and writes them to
+//! `$OUT_DIR/solders_stubs/<module>/<Class>.<id>.pyi` while the calling crate compiles, with
+//! one fragment per impl block. A fragment starts with a `# refs: ...` line listing the other
+//! classes its annotations name, if any.
+//! `stub_writer!` generates the code that later merges the fragments of each class and joins
+//! the classes into one file per module, importing classes from other modules as needed.
+use crate::errors::{self, ErrorKind};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use std::collections::BTreeSet;
+use syn::{
+    Attribute, FnArg, GenericArgument, ImplItem, ImplItemMethod, ItemImpl, PathArguments, Type,
+};
+
+/// The type arguments of the last path segment, e.g. `[T]` for `Vec<T>`.
+fn type_args(segment: &syn::PathSegment) -> Vec<&Type> {
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The Python annotation for a Rust type, falling back to the type's own name.
+pub(crate) fn py_type(ty: &Type, class: &str) -> String {
+    match ty {
+        Type::Reference(r) => py_type(&r.elem, class),
+        Type::Paren(p) => py_type(&p.elem, class),
+        Type::Group(g) => py_type(&g.elem, class),
+        Type::Slice(s) if is_u8(&s.elem) => "bytes".to_owned(),
+        Type::Slice(s) => format!("Sequence[{}]", py_type(&s.elem, class)),
+        Type::Tuple(t) if t.elems.is_empty() => "None".to_owned(),
+        Type::Tuple(t) => {
+            let elems: Vec<_> = t.elems.iter().map(|e| py_type(e, class)).collect();
+            format!("Tuple[{}]", elems.join(", "))
+        }
+        Type::Path(p) if p.qself.is_none() => {
+            let segment = p.path.segments.last().unwrap();
+            let args = type_args(segment);
+            let arg = |i: usize| {
+                args.get(i)
+                    .map_or_else(|| "Any".to_owned(), |ty| py_type(ty, class))
+            };
+            match segment.ident.to_string().as_str() {
+                "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64"
+                | "i128" | "isize" | "CompareOp" => "int".to_owned(),
+                "f32" | "f64" => "float".to_owned(),
+                "bool" => "bool".to_owned(),
+                "String" | "str" => "str".to_owned(),
+                "Self" => class.to_owned(),
+                "PyBytes" => "bytes".to_owned(),
+                "PyDict" => "dict".to_owned(),
+                "PyType" => "type".to_owned(),
+                "PyObject" | "PyAny" => "Any".to_owned(),
+                "Vec" if args.first().is_some_and(|ty| is_u8(ty)) => "bytes".to_owned(),
+                "Vec" => format!("List[{}]", arg(0)),
+                "Option" => format!("Optional[{}]", arg(0)),
+                "HashMap" | "BTreeMap" => format!("Dict[{}, {}]", arg(0), arg(1)),
+                "PyResult" | "Result" | "Py" | "Box" | "PyRef" => arg(0),
+                other => other.to_owned(),
+            }
+        }
+        _ => "Any".to_owned(),
+    }
+}
+
+fn is_u8(ty: &Type) -> bool {
+    matches!(ty, Type::Path(p) if p.path.is_ident("u8"))
+}
+
+/// The names in annotations that aren't classes, e.g. from `typing`.
+const NON_CLASS_NAMES: &[&str] = &[
+    "Any", "Dict", "List", "Optional", "Sequence", "Tuple", "None", "int", "float", "bool", "str",
+    "bytes", "dict", "type",
+];
+
+/// Add the class names in `annotation` other than `class` itself to `refs`.
+fn add_refs(annotation: &str, class: &str, refs: &mut BTreeSet<String>) {
+    let names = annotation.split(|c: char| !(c.is_alphanumeric() || c == '_'));
+    for name in names.filter(|name| !name.is_empty() && *name != class) {
+        if !NON_CLASS_NAMES.contains(&name) {
+            refs.insert(name.to_owned());
+        }
+    }
+}
+
+/// Whether a typed argument stands in for `self`: one named `slf`, or a `PyRef<Self>`,
+/// `PyRefMut<Self>`, `&PyCell<Self>`, `&Bound<Self>` or `Py<Self>`.
+fn is_self_arg(pat_type: &syn::PatType) -> bool {
+    if matches!(&*pat_type.pat, syn::Pat::Ident(pat) if pat.ident == "slf") {
+        return true;
+    }
+    let ty = match &*pat_type.ty {
+        Type::Reference(r) => &*r.elem,
+        ty => ty,
+    };
+    match ty {
+        Type::Path(p) if p.qself.is_none() => {
+            let segment = p.path.segments.last().unwrap();
+            let wrapper = ["PyRef", "PyRefMut", "PyCell", "Bound", "Py"]
+                .iter()
+                .any(|name| segment.ident == name);
+            wrapper
+                && type_args(segment)
+                    .first()
+                    .is_some_and(|ty| matches!(ty, Type::Path(p) if p.path.is_ident("Self")))
+        }
+        _ => false,
+    }
+}
+
+fn has_attr(attrs: &[Attribute], name: &str) -> bool {
+    attrs.iter().any(|a| a.path.is_ident(name))
+}
+
+/// An item's doc comment as a single string.
+pub(crate) fn docstring(attrs: &[Attribute]) -> String {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|a| a.path.is_ident("doc"))
+        .filter_map(|a| match a.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(s),
+                ..
+            })) => Some(s.value()),
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(str::to_owned).unwrap_or(line))
+        .collect();
+    lines.join("\n").trim().to_owned()
+}
+
+/// The stub for one method, indented to sit inside a class body, adding the other classes
+/// it names to `refs`.
+/// Setters are skipped since the getter's `@property` already declares the attribute.
+fn method_stub(
+    method: &ImplItemMethod,
+    class: &str,
+    refs: &mut BTreeSet<String>,
+) -> Option<String> {
+    let attrs = &method.attrs;
+    if has_attr(attrs, "setter") {
+        return None;
+    }
+    let is_new = has_attr(attrs, "new");
+    let is_classmethod = has_attr(attrs, "classmethod");
+    let mut out = String::new();
+    if has_attr(attrs, "staticmethod") {
+        out.push_str("    @staticmethod\n");
+    } else if is_classmethod {
+        out.push_str("    @classmethod\n");
+    } else if has_attr(attrs, "getter") {
+        out.push_str("    @property\n");
+    }
+    let mut params = Vec::new();
+    if method.sig.receiver().is_some() || is_new {
+        params.push("self".to_owned());
+    }
+    let mut typed = method
+        .sig
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Typed(pat_type) if !crate::is_python_token(&pat_type.ty) => Some(pat_type),
+            _ => None,
+        })
+        .peekable();
+    if is_classmethod {
+        typed.next();
+        params.push("cls".to_owned());
+    } else if params.is_empty()
+        && !has_attr(attrs, "staticmethod")
+        && typed.peek().is_some_and(|pat_type| is_self_arg(pat_type))
+    {
+        // e.g. `slf: PyRef<'_, Self>`, which has no `self` receiver.
+        typed.next();
+        params.push("self".to_owned());
+    }
+    for pat_type in typed {
+        let name = match &*pat_type.pat {
+            syn::Pat::Ident(pat) => pat.ident.to_string(),
+            _ => "arg".to_owned(),
+        };
+        let annotation = py_type(&pat_type.ty, class);
+        add_refs(&annotation, class, refs);
+        params.push(format!("{}: {}", name, annotation));
+    }
+    let (name, ret) = if is_new {
+        ("__init__".to_owned(), "None".to_owned())
+    } else {
+        let ret = match &method.sig.output {
+            syn::ReturnType::Default => "None".to_owned(),
+            syn::ReturnType::Type(_, ty) => py_type(ty, class),
+        };
+        add_refs(&ret, class, refs);
+        (method.sig.ident.to_string(), ret)
+    };
+    out.push_str(&format!(
+        "    def {}({}) -> {}:",
+        name,
+        params.join(", "),
+        ret
+    ));
+    let doc = docstring(attrs);
+    if doc.is_empty() {
+        out.push_str(" ...\n");
+    } else {
+        let lines: Vec<String> = doc
+            .lines()
+            .map(|line| match line {
+                "" => String::new(),
+                line => format!("        {}", line),
+            })
+            .collect();
+        out.push_str("\n        \"\"\"");
+        out.push_str(lines.join("\n").trim_start());
+        out.push_str("\"\"\"\n");
+    }
+    Some(out)
+}
+
+/// Render the stub fragment for an impl block, including methods added by other macros.
+fn class_fragment(class: &str, items: &[ImplItem]) -> String {
+    let mut refs = BTreeSet::new();
+    let methods: Vec<String> = items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Method(m) => method_stub(m, class, &mut refs),
+            ImplItem::Verbatim(tokens) => syn::parse2::<ImplItemMethod>(tokens.clone())
+                .ok()
+                .and_then(|m| method_stub(&m, class, &mut refs)),
+            _ => None,
+        })
+        .collect();
+    if refs.is_empty() {
+        methods.join("")
+    } else {
+        let refs: Vec<String> = refs.into_iter().collect();
+        format!("# refs: {}\n{}", refs.join(" "), methods.join(""))
+    }
+}
+
+/// Write the stub fragment for an impl block of `class` to
+/// `$OUT_DIR/solders_stubs/<module>/<class>.<id>.pyi`.
+///
+/// Does nothing if `OUT_DIR` isn't set, i.e. the calling crate has no build script.
+/// Stubs from earlier builds are removed as described in `out_dir`.
+pub(crate) fn write_class_stub(module: &str, class: &str, ast: &ItemImpl) -> syn::Result<()> {
+    if std::env::var_os("OUT_DIR").is_none() {
+        return Ok(());
+    }
+    let fragment = class_fragment(class, &ast.items);
+    let id = crate::out_dir::fragment_id("pyi_stub", ast);
+    let file = std::path::Path::new(module).join(format!("{}.{}.pyi", class, id));
+    crate::out_dir::write("solders_stubs", &file, &fragment)
+        .unwrap_or(Ok(()))
+        .map_err(|e| {
+            errors::at(
+                ErrorKind::Io,
+                Span::call_site(),
+                format!("failed to write stub for `{}`: {}", class, e),
+            )
+        })
+}
+
+/// A `write_stubs(dest)` function joining the stub fragments into `<dest>/<module>.pyi` files.
+pub(crate) fn writer() -> TokenStream {
+    quote! {
+        /// Write the `.pyi` files assembled from the stubs recorded by `#[pyi_stub]`.
+        ///
+        /// Each module gets a `<dest>/<module>.pyi` containing its classes in alphabetical order,
+        /// with the stubs of all of a class's impl blocks merged into one class body.
+        /// Classes named in annotations and stubbed in another module are imported from
+        /// `solders.<module>`.
+        pub fn write_stubs(dest: &std::path::Path) -> std::io::Result<()> {
+            type Classes = std::collections::BTreeMap<String, (std::collections::BTreeSet<String>, String)>;
+            let root = std::path::Path::new(env!("OUT_DIR")).join("solders_stubs");
+            if !root.exists() {
+                return Ok(());
+            }
+            let mut modules = std::collections::BTreeMap::<String, Classes>::new();
+            for module in std::fs::read_dir(&root)? {
+                let module = module?;
+                let module_name = module.file_name().to_string_lossy().into_owned();
+                // Files starting with `.` are the manifests and temporary files of `solders_macros`.
+                if module_name.starts_with('.') {
+                    continue;
+                }
+                let mut fragments: Vec<_> =
+                    std::fs::read_dir(module.path())?.collect::<Result<_, _>>()?;
+                fragments.sort_by_key(|entry| entry.file_name());
+                let classes = modules.entry(module_name).or_default();
+                for fragment in fragments {
+                    let file_name = fragment.file_name().to_string_lossy().into_owned();
+                    let class = file_name.split('.').next().unwrap_or_default().to_owned();
+                    let (refs, body) = classes.entry(class).or_default();
+                    let contents = std::fs::read_to_string(fragment.path())?;
+                    let methods = match contents.strip_prefix("# refs:") {
+                        Some(rest) => {
+                            let (line, methods) = rest.split_once('\n').unwrap_or((rest, ""));
+                            refs.extend(line.split_whitespace().map(str::to_owned));
+                            methods
+                        }
+                        None => contents.as_str(),
+                    };
+                    body.push_str(methods);
+                }
+            }
+            let owners: std::collections::BTreeMap<&str, &str> = modules
+                .iter()
+                .flat_map(|(module, classes)| {
+                    classes.keys().map(move |class| (class.as_str(), module.as_str()))
+                })
+                .collect();
+            std::fs::create_dir_all(dest)?;
+            for (module, classes) in &modules {
+                let mut contents = String::from(
+                    "from typing import Any, Dict, List, Optional, Sequence, Tuple\n",
+                );
+                let imports: std::collections::BTreeSet<String> = classes
+                    .values()
+                    .flat_map(|(refs, _)| refs)
+                    .filter_map(|name| match owners.get(name.as_str()) {
+                        Some(owner) if *owner != module.as_str() => {
+                            Some(format!("from solders.{} import {}\n", owner, name))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                contents.extend(imports);
+                for (class, (_, body)) in classes {
+                    contents.push_str(&format!("\nclass {}:\n", class));
+                    contents.push_str(if body.is_empty() { "    ...\n" } else { body });
+                }
+                std::fs::write(dest.join(format!("{}.pyi", module)), contents)?;
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! `#[wasm_bindgen]` counterparts of generated Python methods, for the `wasm` feature.
+//!
+//! The wasm methods go in their own impl block. Their Rust names get a `wasm_` prefix so they
+//! don't clash with the Python methods, and their JavaScript names are camelCase.
+use crate::errors::{self, ErrorKind};
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Generics, LitStr, Type};
+
+/// The JavaScript name of a method: its snake_case Rust name in camelCase.
+fn js_name(name: &Ident) -> LitStr {
+    let mut camel = String::new();
+    let mut upper = false;
+    for ch in name.to_string().chars() {
+        if ch == '_' {
+            upper = !camel.is_empty();
+        } else if upper {
+            camel.push(ch.to_ascii_uppercase());
+            upper = false;
+        } else {
+            camel.push(ch);
+        }
+    }
+    LitStr::new(&camel, name.span())
+}
+
+/// A `#[wasm_bindgen]` impl block for `self_ty` holding `items`.
+pub(crate) fn impl_block(
+    self_ty: &Type,
+    generics: &Generics,
+    macro_name: &str,
+    items: &[TokenStream],
+) -> syn::Result<TokenStream> {
+    if !generics.params.is_empty() {
+        return Err(errors::spanned(
+            ErrorKind::Generic,
+            generics,
+            format!(
+                "`{}` can't emit wasm bindings for generic types",
+                macro_name
+            ),
+        ));
+    }
+    Ok(quote! {
+        #[wasm_bindgen::prelude::wasm_bindgen]
+        impl #self_ty {
+            #(#items)*
+        }
+    })
+}
+
+/// A getter named `name` returning `body` as `ty`.
+pub(crate) fn getter(
+    docs: &[&syn::Attribute],
+    name: &Ident,
+    ty: &Type,
+    body: TokenStream,
+) -> TokenStream {
+    let js_name = js_name(name);
+    let fn_name = format_ident!("wasm_{}", name);
+    quote! {
+        #(#docs)*
+        #[wasm_bindgen(getter = #js_name)]
+        pub fn #fn_name(&self) -> #ty {
+            #body
+        }
+    }
+}
+
+/// `toBytes`, `fromBytes`, `toJson` and `fromJson` using bincode and serde_json.
+pub(crate) fn serialization_methods() -> Vec<TokenStream> {
+    vec![
+        quote! {
+            /// Serialize to bytes.
+            #[wasm_bindgen(js_name = "toBytes")]
+            pub fn wasm_to_bytes(&self) -> Result<Vec<u8>, wasm_bindgen::JsValue> {
+                bincode::serialize(self).map_err(|e| wasm_bindgen::JsValue::from_str(&e.to_string()))
+            }
+        },
+        quote! {
+            /// Deserialize from bytes.
+            #[wasm_bindgen(js_name = "fromBytes")]
+            pub fn wasm_from_bytes(data: &[u8]) -> Result<Self, wasm_bindgen::JsValue> {
+                bincode::deserialize(data).map_err(|e| wasm_bindgen::JsValue::from_str(&e.to_string()))
+            }
+        },
+        quote! {
+            /// Convert to a JSON string.
+            #[wasm_bindgen(js_name = "toJson")]
+            pub fn wasm_to_json(&self) -> Result<String, wasm_bindgen::JsValue> {
+                serde_json::to_string(self).map_err(|e| wasm_bindgen::JsValue::from_str(&e.to_string()))
+            }
+        },
+        quote! {
+            /// Build from a JSON string.
+            #[wasm_bindgen(js_name = "fromJson")]
+            pub fn wasm_from_json(raw: &str) -> Result<Self, wasm_bindgen::JsValue> {
+                serde_json::from_str(raw).map_err(|e| wasm_bindgen::JsValue::from_str(&e.to_string()))
+            }
+        },
+    ]
+}
@@ -0,0 +1,85 @@
+//! The macros for fixed-size byte types.
+use crate::CommonMethods;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use solders_macros::{
+    array_interface, base58_methods, format_dunder, py_buffer, pyhash, FromBytesLike, WrapperEq,
+};
+
+/// Accepts any bytes-like input for a `Key`.
+#[derive(FromBytesLike)]
+pub struct KeyBytes([u8; 4]);
+
+/// Accepts any bytes-like input that's valid UTF-8.
+#[derive(FromBytesLike)]
+pub struct Utf8Bytes(Utf8);
+
+/// A string built from bytes with `TryFrom<&[u8]>`.
+pub struct Utf8(String);
+
+impl TryFrom<&[u8]> for Utf8 {
+    type Error = std::str::Utf8Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        std::str::from_utf8(bytes).map(|s| Self(s.to_owned()))
+    }
+}
+
+/// A four-byte key.
+#[pyclass(module = "codegen")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, WrapperEq)]
+pub struct Key([u8; 4]);
+
+impl CommonMethods for Key {}
+
+impl From<[u8; 4]> for Key {
+    fn from(bytes: [u8; 4]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl AsRef<[u8]> for Key {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[base58_methods(len = 4, eq)]
+#[format_dunder]
+#[py_buffer]
+#[array_interface]
+#[pyhash(from_bytes)]
+#[solders_macros::pyi_stub(module = "keys")]
+#[pymethods]
+impl Key {
+    #[new]
+    pub fn new(bytes: KeyBytes) -> Self {
+        Self(bytes.0)
+    }
+
+    #[staticmethod]
+    pub fn decode_utf8(bytes: Utf8Bytes) -> String {
+        bytes.0 .0
+    }
+}
+
+/// Exposes its field's bytes.
+#[pyclass(module = "codegen")]
+pub struct Blob {
+    data: Vec<u8>,
+}
+
+#[py_buffer(field = data)]
+#[array_interface(field = data)]
+#[pymethods]
+impl Blob {
+    #[new]
+    pub fn new(data: Vec<u8>) -> Self {
+        Self { data }
+    }
+}
+
+#[solders_macros::pymodule_classes(Key, Blob)]
+pub(crate) fn add_to(m: &Bound<'_, pyo3::types::PyModule>) -> PyResult<()> {
+    Ok(())
+}
@@ -0,0 +1,365 @@
+//! The macros for getters, setters and other class behaviour.
+use pyo3::prelude::*;
+use solders_macros::{
+    all_getters, args_docstring, clone_method, context_manager, deprecated_alias, export_functions,
+    getters_setters, immutable, inner_getters, pyclass_boilerplate, register_class,
+    replace_methods, richcmp_full, signer_methods, solders_pymethods,
+};
+
+/// An account's key and permissions.
+#[all_getters(exclude(note))]
+#[pyclass(module = "codegen")]
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountMeta {
+    /// str: The account's key, upper-cased.
+    #[getter_into(String)]
+    key: Label,
+    /// bool: Whether the account signs.
+    is_signer: bool,
+    note: String,
+}
+
+/// Converted into a `String` by its getter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Label(String);
+
+impl From<Label> for String {
+    fn from(label: Label) -> Self {
+        label.0.to_uppercase()
+    }
+}
+
+#[pyfunction]
+pub fn make_account_meta(key: String, is_signer: bool) -> AccountMeta {
+    AccountMeta {
+        key: Label(key),
+        is_signer,
+        note: String::new(),
+    }
+}
+
+fn check_percentile(value: &u16) -> Result<(), String> {
+    if *value <= 100 {
+        Ok(())
+    } else {
+        Err(format!("percentile must be at most 100, got {}", value))
+    }
+}
+
+/// Settings with validated setters.
+#[getters_setters]
+#[pyclass(module = "codegen")]
+#[allow(dead_code)]
+pub struct Settings {
+    /// int: The fee percentile.
+    #[getset(validate_with = "check_percentile")]
+    percentile: u16,
+    label: String,
+    #[getset(skip)]
+    hidden: u8,
+}
+
+#[pyfunction]
+pub fn make_settings() -> Settings {
+    Settings {
+        percentile: 50,
+        label: "default".to_owned(),
+        hidden: 0,
+    }
+}
+
+/// Copied with some fields replaced.
+#[replace_methods(with_methods)]
+#[pyclass(module = "codegen", get_all)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    name: String,
+    retries: u32,
+}
+
+#[pyfunction]
+pub fn make_config(name: String, retries: u32) -> Config {
+    Config { name, retries }
+}
+
+/// Described by its fields in the class docstring.
+#[args_docstring]
+#[pyclass(module = "codegen")]
+#[allow(dead_code)]
+pub struct Documented {
+    /// Pubkey: The account's key.
+    key: String,
+    /// How many lamports it holds.
+    lamports: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Header {
+    pub num_signatures: u8,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message {
+    pub header: Header,
+    pub blockhash: String,
+}
+
+/// Reads values out of a wrapped message, and can't be modified from Python.
+#[pyclass(module = "codegen")]
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct Transaction(Message);
+
+impl Transaction {
+    fn richcmp(&self, other: &Self, op: pyo3::basic::CompareOp) -> bool {
+        op.matches(self.partial_cmp(other).unwrap())
+    }
+}
+
+impl PartialOrd for Message {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.header
+            .num_signatures
+            .partial_cmp(&other.header.num_signatures)
+    }
+}
+
+#[inner_getters(
+    /// int: The number of required signatures.
+    num_signatures: u8 = self.0.header.num_signatures,
+    /// str: The blockhash.
+    blockhash: String = self.0.blockhash,
+)]
+#[immutable]
+#[clone_method]
+#[richcmp_full]
+#[deprecated_alias(old = "serialize", new = "to_bytes", since = "0.2")]
+#[export_functions(default_transaction)]
+#[register_class(submodule = "registered")]
+#[solders_pymethods]
+impl Transaction {
+    #[new]
+    pub fn new(num_signatures: u8, blockhash: String) -> Self {
+        Self(Message {
+            header: Header { num_signatures },
+            blockhash,
+        })
+    }
+
+    /// Serialize the transaction.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.0.header.num_signatures];
+        bytes.extend_from_slice(self.0.blockhash.as_bytes());
+        bytes
+    }
+
+    /// The default transaction.
+    #[staticmethod]
+    pub fn default_transaction() -> Transaction {
+        Transaction::new(1, "11111111".to_owned())
+    }
+}
+
+/// Opened and closed by `with` and `async with`.
+#[pyclass(module = "codegen")]
+#[derive(Default)]
+pub struct Connection {
+    open: bool,
+    opened: u32,
+}
+
+impl Connection {
+    fn connect(&mut self) -> PyResult<()> {
+        self.open = true;
+        self.opened += 1;
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> PyResult<()> {
+        self.open = false;
+        Ok(())
+    }
+
+    #[cfg(feature = "bound")]
+    fn connect_async<'a>(&mut self, py: Python<'a>) -> PyResult<Bound<'a, PyAny>> {
+        self.connect()?;
+        py.import_bound("asyncio")?.call_method1("sleep", (0,))
+    }
+
+    #[cfg(not(feature = "bound"))]
+    fn connect_async<'a>(&mut self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        self.connect()?;
+        py.import("asyncio")?.call_method1("sleep", (0,))
+    }
+
+    #[cfg(feature = "bound")]
+    fn disconnect_async<'a>(&mut self, py: Python<'a>) -> PyResult<Bound<'a, PyAny>> {
+        self.disconnect()?;
+        py.import_bound("asyncio")?.call_method1("sleep", (0,))
+    }
+
+    #[cfg(not(feature = "bound"))]
+    fn disconnect_async<'a>(&mut self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        self.disconnect()?;
+        py.import("asyncio")?.call_method1("sleep", (0,))
+    }
+}
+
+#[context_manager(
+    open = connect,
+    close = disconnect,
+    async_open = connect_async,
+    async_close = disconnect_async
+)]
+#[pymethods]
+impl Connection {
+    #[new]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[getter]
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    #[getter]
+    pub fn opened(&self) -> u32 {
+        self.opened
+    }
+}
+
+/// Stand-in for solders' `Pubkey`.
+#[pyclass(module = "codegen")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pubkey(pub [u8; 4]);
+
+/// Stand-in for solders' `Signature`.
+#[pyclass(module = "codegen")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signature(pub Vec<u8>);
+
+#[pymethods]
+impl Pubkey {
+    pub fn __bytes__(&self) -> std::borrow::Cow<'_, [u8]> {
+        std::borrow::Cow::Borrowed(&self.0)
+    }
+}
+
+#[pymethods]
+impl Signature {
+    pub fn __bytes__(&self) -> std::borrow::Cow<'_, [u8]> {
+        std::borrow::Cow::Borrowed(&self.0)
+    }
+}
+
+/// Stand-in for the Rust `Signer` trait.
+pub trait SignerTrait {
+    fn pubkey(&self) -> [u8; 4];
+    fn sign_message(&self, message: &[u8]) -> Vec<u8>;
+    fn is_interactive(&self) -> bool;
+}
+
+impl From<[u8; 4]> for Pubkey {
+    fn from(bytes: [u8; 4]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<Vec<u8>> for Signature {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+/// Stand-in for solders' union of signer classes.
+#[derive(FromPyObject)]
+pub enum Signer {
+    Keypair(Keypair),
+}
+
+/// Signs by prefixing the message with its key.
+#[pyclass(module = "codegen")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keypair([u8; 4]);
+
+impl SignerTrait for Keypair {
+    fn pubkey(&self) -> [u8; 4] {
+        self.0
+    }
+
+    fn sign_message(&self, message: &[u8]) -> Vec<u8> {
+        let mut signature = self.0.to_vec();
+        signature.extend_from_slice(message);
+        signature
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+impl Keypair {
+    fn richcmp(&self, other: Signer, op: pyo3::basic::CompareOp) -> PyResult<bool> {
+        let Signer::Keypair(other) = other;
+        match op {
+            pyo3::basic::CompareOp::Eq => Ok(*self == other),
+            pyo3::basic::CompareOp::Ne => Ok(*self != other),
+            _ => Err(pyo3::exceptions::PyTypeError::new_err(
+                "only == and != are supported",
+            )),
+        }
+    }
+}
+
+#[signer_methods(crate = "crate::classes", signer_trait = crate::classes::SignerTrait)]
+#[pymethods]
+impl Keypair {
+    #[new]
+    pub fn new(key: [u8; 4]) -> Self {
+        Self(key)
+    }
+}
+
+/// A frozen class declared with `pyclass_boilerplate`, which adds only the missing `Debug`.
+#[pyclass_boilerplate(module = "classes", frozen, get_all)]
+#[derive(Clone, PartialEq, Eq)]
+pub struct Tag {
+    text: String,
+}
+
+#[pymethods]
+impl Tag {
+    #[new]
+    pub fn new(text: String) -> Self {
+        Self { text }
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+#[solders_macros::pymodule_classes(
+    AccountMeta,
+    Settings,
+    Config,
+    Documented,
+    Transaction,
+    Connection,
+    Pubkey,
+    Signature,
+    Keypair,
+    Tag
+)]
+pub(crate) fn add_to(m: &Bound<'_, pyo3::types::PyModule>) -> PyResult<()> {
+    m.add_function(pyo3::wrap_pyfunction!(make_account_meta, m)?)?;
+    m.add_function(pyo3::wrap_pyfunction!(make_settings, m)?)?;
+    m.add_function(pyo3::wrap_pyfunction!(make_config, m)?)?;
+    #[cfg(feature = "bound")]
+    Transaction::register_functions(m)?;
+    #[cfg(not(feature = "bound"))]
+    Transaction::register_functions(m.as_gil_ref())?;
+    Ok(())
+}
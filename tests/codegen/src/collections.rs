@@ -0,0 +1,56 @@
+//! The container protocol macros.
+use pyo3::prelude::*;
+use solders_macros::{
+    bool_dunder, concat_dunders, len_dunder, mapping_methods, pyiterator, sizeof_dunder,
+};
+use std::collections::HashMap;
+
+/// A read-only mapping from names to balances.
+#[pyclass(module = "codegen")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Balances(HashMap<String, u64>);
+
+#[mapping_methods(field = 0, key = String, value = u64)]
+#[pymethods]
+impl Balances {
+    #[new]
+    pub fn new(balances: HashMap<String, u64>) -> Self {
+        Self(balances)
+    }
+}
+
+/// A list of numbers, iterated lazily.
+#[pyclass(module = "codegen")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Numbers {
+    items: Vec<u64>,
+}
+
+impl Numbers {
+    fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+#[pyiterator(item = u64, field = items)]
+#[len_dunder(field = items)]
+#[bool_dunder(falsy_if = is_empty)]
+#[concat_dunders(field = items)]
+#[sizeof_dunder(heap(items))]
+#[pymethods]
+impl Numbers {
+    #[new]
+    pub fn new(items: Vec<u64>) -> Self {
+        Self { items }
+    }
+
+    #[getter]
+    pub fn items(&self) -> Vec<u64> {
+        self.items.clone()
+    }
+}
+
+#[solders_macros::pymodule_classes(Balances, Numbers)]
+pub(crate) fn add_to(m: &Bound<'_, pyo3::types::PyModule>) -> PyResult<()> {
+    Ok(())
+}
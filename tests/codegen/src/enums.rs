@@ -0,0 +1,113 @@
+//! The enum macros and derives.
+use pyo3::prelude::*;
+use solders_macros::{
+    complex_enum, enum_original_mapping, enum_predicates, enum_str_mapping, int_dunders, int_enum,
+    EnumFromPy, EnumIntoPy,
+};
+
+/// Behaves like an `enum.IntEnum`.
+#[int_enum]
+#[pyclass(module = "codegen")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Level {
+    Low = 1,
+    High = 2,
+}
+
+/// Converts to and from ints.
+#[int_dunders]
+#[pyclass(module = "codegen")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    Read,
+    Write,
+}
+
+/// Converts to and from strings.
+#[enum_str_mapping(rename_all = "camelCase")]
+#[pyclass(module = "codegen")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Commitment {
+    Processed,
+    FullyConfirmed,
+}
+
+/// Has an `is_<variant>()` per variant.
+#[enum_predicates]
+#[pyclass(module = "codegen")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Status {
+    Pending,
+    AlreadyProcessed,
+}
+
+/// The Rust enum `Status` mirrors.
+#[derive(Debug, PartialEq)]
+pub enum StatusOriginal {
+    Pending,
+    AlreadyProcessed,
+}
+
+/// Mapped to and from `StatusOriginal`.
+#[enum_original_mapping(StatusOriginal)]
+#[derive(Debug, PartialEq)]
+pub enum StatusMirror {
+    Pending,
+    AlreadyProcessed,
+}
+
+/// Either a `Mode` or a `Level`, converted to and from whichever it holds.
+#[derive(Clone, Debug, PartialEq, EnumIntoPy, EnumFromPy)]
+pub enum ModeOrLevel {
+    Mode(Mode),
+    Level(Level),
+}
+
+#[pyfunction]
+pub fn echo_mode_or_level(value: ModeOrLevel) -> ModeOrLevel {
+    value
+}
+
+/// Builds the list with `PyList::new_bound`, which needs `ToPyObject`.
+#[cfg(feature = "bound")]
+#[pyfunction]
+pub fn mode_or_level_list(py: Python<'_>, values: Vec<ModeOrLevel>) -> PyObject {
+    pyo3::types::PyList::new_bound(py, &values).into()
+}
+
+/// One class per variant.
+#[complex_enum(module = "codegen")]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Instruction {
+    /// Does nothing.
+    Noop,
+    /// Moves by an offset.
+    Move(i64, i64),
+    /// Transfers an amount.
+    Transfer {
+        /// The amount to transfer.
+        amount: u64,
+    },
+}
+
+#[pyfunction]
+pub fn echo_instruction(value: Instruction) -> Instruction {
+    value
+}
+
+#[solders_macros::pymodule_classes(
+    Level,
+    Mode,
+    Commitment,
+    Status,
+    InstructionNoop,
+    InstructionMove,
+    InstructionTransfer
+)]
+pub(crate) fn add_to(m: &Bound<'_, pyo3::types::PyModule>) -> PyResult<()> {
+    m.add_function(pyo3::wrap_pyfunction!(echo_mode_or_level, m)?)?;
+    #[cfg(feature = "bound")]
+    m.add_function(pyo3::wrap_pyfunction!(mode_or_level_list, m)?)?;
+    m.add_function(pyo3::wrap_pyfunction!(echo_instruction, m)?)?;
+    Ok(())
+}
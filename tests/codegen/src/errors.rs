@@ -0,0 +1,53 @@
+//! The error macros.
+use pyo3::prelude::*;
+use solders_macros::{py_exceptions, IntoPyErr};
+
+pyo3::create_exception!(codegen, ParseError, pyo3::exceptions::PyException);
+
+/// Raised as one exception class per variant.
+#[py_exceptions(module = "codegen.errors")]
+#[derive(Debug, thiserror::Error)]
+pub enum SignerError {
+    #[error("keypair-pubkey mismatch")]
+    KeypairPubkeyMismatch,
+    #[error("not enough signers: {0}")]
+    NotEnoughSigners(usize),
+}
+
+/// Raised as the exception chosen per variant.
+#[derive(Debug, thiserror::Error, IntoPyErr)]
+#[pyerr(ValueError)]
+pub enum WrapperError {
+    #[error("bad input: {0}")]
+    Input(String),
+    #[error("unparseable")]
+    #[pyerr(custom = ParseError, debug)]
+    Parse,
+}
+
+#[pyfunction]
+pub fn raise_signer_error(missing: usize) -> PyResult<()> {
+    match missing {
+        0 => Err(SignerError::KeypairPubkeyMismatch.into()),
+        n => Err(SignerError::NotEnoughSigners(n).into()),
+    }
+}
+
+#[pyfunction]
+pub fn raise_wrapper_error(input: &str) -> PyResult<()> {
+    match input {
+        "" => Err(WrapperError::Parse.into()),
+        other => Err(WrapperError::Input(other.to_owned()).into()),
+    }
+}
+
+pub(crate) fn add_to(m: &Bound<'_, pyo3::types::PyModule>) -> PyResult<()> {
+    #[cfg(feature = "bound")]
+    SignerError::register_exceptions(m.py(), m)?;
+    #[cfg(not(feature = "bound"))]
+    SignerError::register_exceptions(m.py(), m.as_gil_ref())?;
+    m.add("ParseError", m.py().get_type_bound::<ParseError>())?;
+    m.add_function(pyo3::wrap_pyfunction!(raise_signer_error, m)?)?;
+    m.add_function(pyo3::wrap_pyfunction!(raise_wrapper_error, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,147 @@
+//! Applies the macros to real `#[pyclass]` types, so their output is compiled against pyo3.
+//! The tests in `tests/` then use the classes from Python.
+//!
+//! Without the `bound` feature the generated code uses GIL refs, which pyo3 0.22 still has
+//! behind its `gil-refs` feature.
+#![cfg_attr(not(feature = "bound"), allow(deprecated))]
+
+use pyo3::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+pub mod bytes;
+pub mod classes;
+pub mod collections;
+pub mod enums;
+pub mod errors;
+pub mod serialization;
+pub mod stubs;
+
+solders_macros::class_registry!();
+solders_macros::stub_writer!();
+
+/// A `bytes` object tied to the GIL lifetime `'a`, as returned by `CommonMethods::pybytes`.
+#[cfg(feature = "bound")]
+pub type PyBytesRef<'a> = Bound<'a, pyo3::types::PyBytes>;
+/// A `bytes` object tied to the GIL lifetime `'a`, as returned by `CommonMethods::pybytes`.
+#[cfg(not(feature = "bound"))]
+pub type PyBytesRef<'a> = &'a pyo3::types::PyBytes;
+
+fn new_bytes<'a>(py: Python<'a>, data: &[u8]) -> PyBytesRef<'a> {
+    #[cfg(feature = "bound")]
+    return pyo3::types::PyBytes::new_bound(py, data);
+    #[cfg(not(feature = "bound"))]
+    return pyo3::types::PyBytes::new(py, data);
+}
+
+fn value_error(e: impl std::fmt::Display) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(e.to_string())
+}
+
+/// Stand-in for solders' `CommonMethods`, serializing to JSON for both bytes and strings.
+pub trait CommonMethods: Serialize + DeserializeOwned + Clone + IntoPy<PyObject> {
+    fn pybytes<'a>(&self, py: Python<'a>) -> PyBytesRef<'a> {
+        new_bytes(py, self.py_to_json().as_bytes())
+    }
+
+    fn py_from_bytes(data: &[u8]) -> PyResult<Self> {
+        serde_json::from_slice(data).map_err(value_error)
+    }
+
+    fn py_to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    fn py_from_json(raw: &str) -> PyResult<Self> {
+        serde_json::from_str(raw).map_err(value_error)
+    }
+
+    fn pystr(&self) -> String {
+        self.py_to_json()
+    }
+
+    fn pyrepr(&self) -> String {
+        self.py_to_json()
+    }
+
+    #[cfg(feature = "bound")]
+    fn pyreduce<'a>(&self, py: Python<'a>) -> PyResult<(Bound<'a, PyAny>, Bound<'a, PyAny>)> {
+        let constructor = self
+            .clone()
+            .into_py(py)
+            .into_bound(py)
+            .getattr("from_bytes")?;
+        let args = pyo3::types::PyTuple::new_bound(py, [self.pybytes(py)]);
+        Ok((constructor, args.into_any()))
+    }
+
+    #[cfg(not(feature = "bound"))]
+    fn pyreduce(&self) -> PyResult<(PyObject, PyObject)> {
+        Python::with_gil(|py| {
+            let constructor = self.clone().into_py(py).getattr(py, "from_bytes")?;
+            Ok((constructor, (self.pybytes(py),).into_py(py)))
+        })
+    }
+}
+
+/// Stand-in for solders' `PyHash`.
+pub trait PyHash: std::hash::Hash {
+    fn pyhash(&self) -> u64 {
+        use std::hash::Hasher;
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Stand-in for solders' `RichcmpEqualityOnly`.
+pub trait RichcmpEqualityOnly: PartialEq {
+    fn richcmp(&self, other: &Self, op: pyo3::basic::CompareOp) -> PyResult<bool> {
+        match op {
+            pyo3::basic::CompareOp::Eq => Ok(self == other),
+            pyo3::basic::CompareOp::Ne => Ok(self != other),
+            _ => Err(pyo3::exceptions::PyTypeError::new_err(
+                "only == and != are supported",
+            )),
+        }
+    }
+}
+
+/// Build the `codegen` Python module holding every class in this crate, and insert it into
+/// `sys.modules` so pickle can find the classes.
+pub fn module(py: Python<'_>) -> PyResult<Bound<'_, pyo3::types::PyModule>> {
+    let sys_modules = py.import_bound("sys")?.getattr("modules")?;
+    if let Ok(m) = sys_modules.get_item("codegen") {
+        return Ok(m.downcast_into()?);
+    }
+    let m = pyo3::types::PyModule::new_bound(py, "codegen")?;
+    bytes::add_to(&m)?;
+    classes::add_to(&m)?;
+    collections::add_to(&m)?;
+    enums::add_to(&m)?;
+    errors::add_to(&m)?;
+    serialization::add_to(&m)?;
+    stubs::add_to(&m)?;
+    #[cfg(feature = "bound")]
+    register_all(py, &m)?;
+    #[cfg(not(feature = "bound"))]
+    register_all(py, m.as_gil_ref())?;
+    sys_modules.set_item("codegen", &m)?;
+    Ok(m)
+}
+
+/// Run `code` as a Python module named `name`, with `codegen` importable, panicking with the
+/// Python traceback if it raises.
+pub fn run_python(name: &str, code: &str) {
+    Python::with_gil(|py| {
+        let result = module(py).and_then(|_| {
+            pyo3::types::PyModule::from_code_bound(py, code, &format!("{}.py", name), name)
+        });
+        if let Err(e) = result {
+            let traceback = e
+                .traceback_bound(py)
+                .and_then(|tb| tb.format().ok())
+                .unwrap_or_default();
+            panic!("{}{}", traceback, e);
+        }
+    });
+}
@@ -0,0 +1,331 @@
+//! The serialization and pickling macros.
+use crate::{value_error, CommonMethods, PyBytesRef, PyHash, RichcmpEqualityOnly};
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use solders_macros::{
+    base64_methods, borsh_methods, common_methods, common_methods_rpc_resp_no_context,
+    copy_methods, debug_repr, getnewargs, json_file_methods, json_schema, msgpack_methods,
+    pickle_buffer, pickle_state, pyhash, richcmp_eq_only, rpc_id_getter, str_methods,
+    versioned_methods, yaml_methods,
+};
+
+/// A class using the default `common_methods` and most of the format macros.
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[pyclass(module = "codegen")]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct Plain {
+    name: String,
+    value: u64,
+}
+
+// Written out rather than derived, so the test crate doesn't need `borsh-derive`.
+impl borsh::BorshSerialize for Plain {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        borsh::BorshSerialize::serialize(&self.name, writer)?;
+        borsh::BorshSerialize::serialize(&self.value, writer)
+    }
+}
+
+impl borsh::BorshDeserialize for Plain {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        Ok(Self {
+            name: String::deserialize_reader(reader)?,
+            value: u64::deserialize_reader(reader)?,
+        })
+    }
+}
+
+impl PyHash for Plain {}
+impl RichcmpEqualityOnly for Plain {}
+impl CommonMethods for Plain {}
+
+#[common_methods]
+#[pyhash]
+#[richcmp_eq_only]
+#[copy_methods]
+#[json_file_methods]
+#[yaml_methods]
+#[msgpack_methods]
+#[json_schema]
+#[base64_methods]
+#[borsh_methods]
+// With `bound`, `dict_methods` targets pythonize 0.22, whose `depythonize` takes a `Bound`.
+#[cfg_attr(not(feature = "bound"), solders_macros::dict_methods)]
+#[pymethods]
+impl Plain {
+    #[new]
+    pub fn new(name: String, value: u64) -> Self {
+        Self { name, value }
+    }
+}
+
+/// Pickled by calling the constructor with the serialized bytes.
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[pyclass(module = "codegen")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NewArgs {
+    value: u64,
+}
+
+impl CommonMethods for NewArgs {}
+
+#[common_methods(reduce = "newargs")]
+#[pymethods]
+impl NewArgs {
+    #[new]
+    pub fn new(data: &[u8]) -> PyResult<Self> {
+        Self::py_from_bytes(data)
+    }
+
+    #[staticmethod]
+    pub fn from_value(value: u64) -> Self {
+        Self { value }
+    }
+}
+
+/// Pickled out of band with protocol 5.
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[pyclass(module = "codegen")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Buffered {
+    data: Vec<u8>,
+}
+
+impl CommonMethods for Buffered {}
+
+#[common_methods]
+#[pickle_buffer]
+#[pymethods]
+impl Buffered {
+    #[new]
+    pub fn new(data: Vec<u8>) -> Self {
+        Self { data }
+    }
+}
+
+/// Pickled through `__getstate__` and `__setstate__`.
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[pyclass(module = "codegen", subclass)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Stateful {
+    value: u64,
+}
+
+impl CommonMethods for Stateful {}
+
+#[common_methods]
+#[pickle_state]
+#[getnewargs]
+#[pymethods]
+impl Stateful {
+    #[new]
+    #[pyo3(signature = (value = 0))]
+    pub fn new(value: u64) -> Self {
+        Self { value }
+    }
+
+    #[getter]
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+}
+
+/// Pickled through `__getstate__`, and built from that state by its constructor.
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+#[pyclass(module = "codegen", frozen, subclass)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FrozenState {
+    value: u64,
+}
+
+impl CommonMethods for FrozenState {}
+
+#[common_methods]
+#[pickle_state(frozen)]
+#[pymethods]
+impl FrozenState {
+    #[new]
+    pub fn new(data: &[u8]) -> PyResult<Self> {
+        Self::py_from_bytes(data)
+    }
+
+    #[staticmethod]
+    pub fn from_value(value: u64) -> Self {
+        Self { value }
+    }
+
+    #[getter]
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+}
+
+/// Pickled by calling the constructor with its original arguments.
+#[pyclass(module = "codegen")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Point {
+    x: i64,
+    y: i64,
+}
+
+#[getnewargs]
+#[pymethods]
+impl Point {
+    #[new]
+    pub fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    #[getter]
+    pub fn x(&self) -> i64 {
+        self.x
+    }
+
+    #[getter]
+    pub fn y(&self) -> i64 {
+        self.y
+    }
+}
+
+/// Serialized with a one-byte version prefix.
+#[pyclass(module = "codegen")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Versioned(u8);
+
+impl Versioned {
+    fn pybytes_versioned<'a>(&self, py: Python<'a>) -> PyBytesRef<'a> {
+        crate::new_bytes(py, &[1, self.0])
+    }
+
+    fn py_from_bytes_versioned(data: &[u8]) -> PyResult<Self> {
+        match data {
+            [1, value] => Ok(Self(*value)),
+            _ => Err(value_error("expected version 1")),
+        }
+    }
+
+    fn pybytes_legacy<'a>(&self, py: Python<'a>) -> PyBytesRef<'a> {
+        crate::new_bytes(py, &[self.0])
+    }
+
+    fn py_from_bytes_legacy(data: &[u8]) -> PyResult<Self> {
+        match data {
+            [value] => Ok(Self(*value)),
+            _ => Err(value_error("expected one byte")),
+        }
+    }
+
+    fn pyversion(&self, py: Python<'_>) -> PyObject {
+        1.into_py(py)
+    }
+}
+
+#[versioned_methods]
+#[pymethods]
+impl Versioned {
+    #[new]
+    pub fn new(value: u8) -> Self {
+        Self(value)
+    }
+}
+
+/// Round-trips through its `Display` output.
+#[pyclass(module = "codegen")]
+#[derive(Clone, PartialEq)]
+pub struct Text(String);
+
+impl std::fmt::Debug for Text {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+impl std::fmt::Display for Text {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::str::FromStr for Text {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            Err("empty text".to_owned())
+        } else {
+            Ok(Self(s.to_owned()))
+        }
+    }
+}
+
+#[str_methods]
+#[debug_repr(wrap)]
+#[pymethods]
+impl Text {}
+
+pub mod rpc {
+    pub mod responses {
+        /// Stand-in for solders' RPC response union, which is either the result or an error.
+        pub type Resp<T> = T;
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RequestBase {
+    pub id: u64,
+}
+
+/// An RPC request with the `id` in its `base`.
+#[pyclass(module = "codegen")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GetBalance {
+    base: RequestBase,
+}
+
+impl RichcmpEqualityOnly for GetBalance {}
+impl CommonMethods for GetBalance {}
+
+#[common_methods_rpc_resp_no_context]
+#[rpc_id_getter]
+#[pymethods]
+impl GetBalance {
+    #[new]
+    pub fn new(id: u64) -> Self {
+        Self {
+            base: RequestBase { id },
+        }
+    }
+}
+
+/// An RPC response, whose `from_json` returns `Resp<Self>`.
+#[pyclass(module = "codegen")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BalanceResp(u64);
+
+impl RichcmpEqualityOnly for BalanceResp {}
+impl CommonMethods for BalanceResp {}
+
+#[solders_macros::common_methods_rpc_resp(crate = "crate::serialization")]
+#[pymethods]
+impl BalanceResp {
+    #[new]
+    pub fn new(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+#[solders_macros::pymodule_classes(
+    Plain,
+    NewArgs,
+    Buffered,
+    Stateful,
+    FrozenState,
+    Point,
+    Versioned,
+    Text,
+    GetBalance,
+    BalanceResp
+)]
+pub(crate) fn add_to(m: &Bound<'_, pyo3::types::PyModule>) -> PyResult<()> {
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! The `.pyi` stubs recorded by `pyi_stub`.
+use crate::bytes::Key;
+use pyo3::prelude::*;
+use solders_macros::pyi_stub;
+
+/// Two keys, with methods split over impl blocks when pyo3 allows more than one.
+#[pyclass(module = "codegen")]
+#[derive(Clone)]
+pub struct KeyPair {
+    left: Key,
+    right: Key,
+}
+
+#[pyi_stub(module = "pairs")]
+#[pymethods]
+impl KeyPair {
+    #[new]
+    pub fn new(left: Key, right: Key) -> Self {
+        Self { left, right }
+    }
+
+    /// The first key.
+    #[getter]
+    pub fn left(slf: PyRef<'_, Self>) -> Key {
+        slf.left.clone()
+    }
+
+    /// The second key.
+    #[getter]
+    pub fn right(&self) -> Key {
+        self.right.clone()
+    }
+}
+
+#[cfg(feature = "multiple-pymethods")]
+#[pyi_stub(module = "pairs")]
+#[pymethods]
+impl KeyPair {
+    pub fn swapped(&self) -> KeyPair {
+        Self {
+            left: self.right.clone(),
+            right: self.left.clone(),
+        }
+    }
+}
+
+#[solders_macros::pymodule_classes(KeyPair)]
+pub(crate) fn add_to(m: &Bound<'_, pyo3::types::PyModule>) -> PyResult<()> {
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use solders_macros_codegen_tests::run_python;
+
+#[test]
+fn byte_types() {
+    run_python(
+        "byte_types",
+        r#"
+from codegen import Blob, Key
+
+k = Key(b"\x01\x02\x03\x04")
+assert Key(bytearray(b"\x01\x02\x03\x04")) == k
+assert Key(memoryview(b"\x01\x02\x03\x04")) == k
+assert Key([1, 2, 3, 4]) == k
+try:
+    Key(b"\x01")
+except ValueError as e:
+    assert "expected 4 bytes, got 1" in str(e)
+else:
+    raise AssertionError("the wrong length should be rejected")
+try:
+    Key([1, 2, 256, 4])
+except ValueError as e:
+    assert str(e) == "byte at index 2 must be in range(0, 256), got 256", str(e)
+else:
+    raise AssertionError("out-of-range ints should be rejected")
+try:
+    Key([1, -1, 3, 4])
+except ValueError as e:
+    assert "index 1" in str(e) and "got -1" in str(e), str(e)
+else:
+    raise AssertionError("negative ints should be rejected")
+try:
+    Key([1, "2", 3, 4])
+except TypeError as e:
+    assert str(e).endswith("expected a sequence of ints, got str at index 1"), str(e)
+else:
+    raise AssertionError("non-int elements should be rejected")
+try:
+    Key(1.5)
+except TypeError:
+    pass
+else:
+    raise AssertionError("floats should be rejected")
+
+s = k.to_base58()
+assert Key.from_string(s) == k
+assert k == s and k == b"\x01\x02\x03\x04" and k != "not base58!"
+assert f"{k}" == s and f"{k:hex}" == "01020304"
+assert bytes(memoryview(k)) == b"\x01\x02\x03\x04"
+assert k.__array_interface__["shape"] == (4,)
+assert hash(k) == hash(Key([1, 2, 3, 4]))
+assert Key.decode_utf8(b"hi") == "hi"
+try:
+    Key.decode_utf8(b"\xff")
+except ValueError:
+    pass
+else:
+    raise AssertionError("invalid UTF-8 should be rejected")
+
+b = Blob(b"xyz")
+assert bytes(memoryview(b)) == b"xyz"
+assert b.__array_interface__["typestr"] == "|u1"
+"#,
+    );
+}
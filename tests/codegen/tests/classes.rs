@@ -0,0 +1,124 @@
+use solders_macros_codegen_tests::run_python;
+
+#[test]
+fn getters_and_setters() {
+    run_python(
+        "getters_and_setters",
+        r#"
+from codegen import Config, Documented, make_account_meta, make_config, make_settings
+
+meta = make_account_meta("abc", True)
+assert meta.key == "ABC" and meta.is_signer is True
+assert not hasattr(meta, "note")
+
+s = make_settings()
+s.percentile = 90
+assert s.percentile == 90
+try:
+    s.percentile = 101
+except ValueError as e:
+    assert "at most 100" in str(e)
+else:
+    raise AssertionError("the validator should reject 101")
+s.label = "x"
+assert s.label == "x"
+assert not hasattr(s, "hidden")
+
+c = make_config("a", 1)
+assert c.replace(retries=3).retries == 3 and c.retries == 1
+assert c.with_name("b").name == "b"
+try:
+    c.replace(bogus=1)
+except TypeError:
+    pass
+else:
+    raise AssertionError("unknown fields should be rejected")
+
+assert "key (Pubkey): The account's key." in Documented.__doc__, Documented.__doc__
+"#,
+    );
+}
+
+#[test]
+fn transaction_methods() {
+    run_python(
+        "transaction_methods",
+        r#"
+import warnings
+import codegen
+from codegen import Transaction
+from codegen.registered import Transaction as Registered
+
+assert Registered is Transaction
+tx = Transaction(2, "abc")
+assert (tx.num_signatures, tx.blockhash) == (2, "abc")
+assert tx.clone() == tx and tx.clone() is not tx
+assert Transaction(1, "a") < tx <= tx
+try:
+    tx.blockhash = "x"
+except AttributeError as e:
+    assert "replace()" in str(e)
+else:
+    raise AssertionError("Transaction should be immutable")
+with warnings.catch_warnings(record=True) as caught:
+    warnings.simplefilter("always")
+    assert tx.serialize() == tx.to_bytes() == list(b"\x02abc")
+assert caught and issubclass(caught[0].category, DeprecationWarning)
+assert codegen.default_transaction() == Transaction.default_transaction()
+"#,
+    );
+}
+
+#[test]
+fn context_manager_and_signer() {
+    run_python(
+        "context_manager_and_signer",
+        r#"
+import asyncio
+from codegen import Connection, Keypair
+
+c = Connection()
+with c as opened:
+    assert opened is c and c.is_open
+assert not c.is_open
+
+async def use():
+    async with c:
+        assert c.is_open
+    assert not c.is_open
+
+asyncio.run(use())
+assert c.opened == 2
+
+k = Keypair([1, 2, 3, 4])
+assert bytes(k.pubkey()) == b"\x01\x02\x03\x04"
+assert bytes(k.sign_message(b"m")) == b"\x01\x02\x03\x04m"
+assert not k.is_interactive()
+assert k == Keypair([1, 2, 3, 4]) and k != Keypair([0, 0, 0, 0])
+"#,
+    );
+}
+
+#[test]
+fn pyclass_boilerplate() {
+    run_python(
+        "pyclass_boilerplate",
+        r#"
+from codegen import Tag
+
+class Sub(Tag):
+    pass
+
+label = Sub("x")
+assert label.text == "x" and isinstance(label, Tag)
+assert Tag.__module__ == "solders.classes"
+assert repr(Tag("x")) == 'Tag { text: "x" }'
+try:
+    label.text = "y"
+except AttributeError:
+    pass
+else:
+    raise AssertionError("Tag should be frozen")
+"#,
+    );
+}
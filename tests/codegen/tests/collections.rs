@@ -0,0 +1,52 @@
+use solders_macros_codegen_tests::run_python;
+
+#[test]
+fn mapping() {
+    run_python(
+        "mapping",
+        r#"
+from codegen import Balances
+
+b = Balances({"a": 1, "b": 2})
+assert len(b) == 2
+assert b["a"] == 1
+assert "a" in b and "c" not in b and 1 not in b and None not in b
+assert sorted(b) == ["a", "b"]
+assert b.get("a") == 1 and b.get("c") is None and b.get("c", 0) == 0
+assert b.get(1) is None and b.get(1, default=5) == 5
+try:
+    b["c"]
+except KeyError as e:
+    assert e.args == ("c",)
+else:
+    raise AssertionError("missing key should raise KeyError")
+assert sorted(b.keys()) == ["a", "b"]
+assert sorted(b.values()) == [1, 2]
+assert sorted(b.items()) == [("a", 1), ("b", 2)]
+"#,
+    );
+}
+
+#[test]
+fn iteration_and_sequence_dunders() {
+    run_python(
+        "iteration_and_sequence_dunders",
+        r#"
+import sys
+from codegen import Numbers
+
+n = Numbers([1, 2, 3])
+assert list(n) == [1, 2, 3]
+it = iter(n)
+assert iter(it) is it
+assert next(it) == 1
+assert list(it) == [2, 3]
+assert len(n) == 3
+assert n and not Numbers([])
+assert (n + Numbers([4])).items == [1, 2, 3, 4]
+n += Numbers([5])
+assert n.items == [1, 2, 3, 5]
+assert sys.getsizeof(Numbers(list(range(100)))) > sys.getsizeof(Numbers([]))
+"#,
+    );
+}
@@ -0,0 +1,64 @@
+use solders_macros_codegen_tests::run_python;
+
+#[test]
+fn int_and_string_enums() {
+    run_python(
+        "int_and_string_enums",
+        r#"
+from codegen import Commitment, Level, Mode, Status
+
+assert int(Level.High) == 2
+assert Level.Low < Level.High and Level.High == 2 and Level.Low < 2
+assert hash(Level.High) == hash(2)
+assert Level.from_int(1) == Level.Low
+assert int(Mode.Write) == 1 and [0, 1][Mode.Write] == 1
+assert Mode.from_int(0) == Mode.Read
+try:
+    Mode.from_int(5)
+except ValueError:
+    pass
+else:
+    raise AssertionError("out-of-range ints should be rejected")
+
+assert str(Commitment.FullyConfirmed) == "fullyConfirmed"
+assert Commitment.from_string("processed") == Commitment.Processed
+assert Status.AlreadyProcessed.is_already_processed()
+assert not Status.Pending.is_already_processed()
+"#,
+    );
+}
+
+#[test]
+fn union_and_complex_enums() {
+    run_python(
+        "union_and_complex_enums",
+        r#"
+import codegen
+from codegen import (
+    InstructionMove,
+    InstructionNoop,
+    InstructionTransfer,
+    Level,
+    Mode,
+    echo_instruction,
+    echo_mode_or_level,
+)
+
+assert echo_mode_or_level(Mode.Write) == Mode.Write
+assert echo_mode_or_level(Level.High) == Level.High
+if hasattr(codegen, "mode_or_level_list"):
+    assert codegen.mode_or_level_list([Mode.Write, Level.Low]) == [Mode.Write, Level.Low]
+try:
+    echo_mode_or_level("neither")
+except TypeError:
+    pass
+else:
+    raise AssertionError("other types should be rejected")
+
+assert isinstance(echo_instruction(InstructionNoop()), InstructionNoop)
+moved = echo_instruction(InstructionMove(1, -1))
+assert (moved.field_0, moved.field_1) == (1, -1)
+assert echo_instruction(InstructionTransfer(5)).amount == 5
+"#,
+    );
+}
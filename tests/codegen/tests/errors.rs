@@ -0,0 +1,38 @@
+use solders_macros_codegen_tests::run_python;
+
+#[test]
+fn exceptions() {
+    run_python(
+        "exceptions",
+        r#"
+from codegen import (
+    KeypairPubkeyMismatch,
+    NotEnoughSigners,
+    ParseError,
+    SignerError,
+    raise_signer_error,
+    raise_wrapper_error,
+)
+
+assert issubclass(NotEnoughSigners, SignerError)
+assert NotEnoughSigners.__module__ == "codegen.errors"
+try:
+    raise_signer_error(2)
+except NotEnoughSigners as e:
+    assert str(e) == "not enough signers: 2"
+try:
+    raise_signer_error(0)
+except KeypairPubkeyMismatch as e:
+    assert str(e) == "keypair-pubkey mismatch"
+
+try:
+    raise_wrapper_error("x")
+except ValueError as e:
+    assert str(e) == "bad input: x"
+try:
+    raise_wrapper_error("")
+except ParseError as e:
+    assert str(e) == "Parse"
+"#,
+    );
+}
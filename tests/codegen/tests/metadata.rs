@@ -0,0 +1,24 @@
+//! The `metadata` feature writes one file per macro and impl block to `$OUT_DIR`.
+#![cfg(feature = "metadata")]
+
+#[test]
+fn metadata_files() {
+    let dir = std::path::Path::new(env!("OUT_DIR")).join("solders_metadata");
+    let mut names: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    let count = |prefix: &str| names.iter().filter(|n| n.starts_with(prefix)).count();
+    assert_eq!(count("Plain.common_methods."), 1, "{:?}", names);
+    assert_eq!(count("Stateful.pickle_state."), 1, "{:?}", names);
+    assert!(count(".") >= 1, "no manifest in {:?}", names);
+    assert!(!names.iter().any(|n| n.ends_with(".tmp")), "{:?}", names);
+    let plain = names
+        .iter()
+        .find(|n| n.starts_with("Plain.common_methods."))
+        .unwrap();
+    let json = std::fs::read_to_string(dir.join(plain)).unwrap();
+    assert!(json.starts_with("{\"class\": \"Plain\", \"macro\": \"common_methods\""));
+    assert!(json.contains("\"name\": \"from_json\""), "{}", json);
+}
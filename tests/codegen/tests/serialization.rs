@@ -0,0 +1,176 @@
+use solders_macros_codegen_tests::run_python;
+
+#[test]
+fn common_methods_round_trip() {
+    run_python(
+        "common_methods_round_trip",
+        r#"
+import copy
+import pickle
+from codegen import Plain
+
+p = Plain("a", 1)
+assert Plain.from_bytes(bytes(p)) == p
+assert Plain.from_json(p.to_json()) == p
+assert str(p) == p.to_json()
+assert repr(p) == p.to_json()
+assert pickle.loads(pickle.dumps(p)) == p
+assert copy.copy(p) == p and copy.copy(p) is not p
+assert copy.deepcopy(p) == p
+assert p.clone() == p and p.clone() is not p
+assert hash(p) == hash(Plain("a", 1))
+assert p != Plain("a", 2)
+try:
+    p < p
+except TypeError:
+    pass
+else:
+    raise AssertionError("ordering should raise TypeError")
+"#,
+    );
+}
+
+#[test]
+fn format_methods_round_trip() {
+    run_python(
+        "format_methods_round_trip",
+        r#"
+import base64
+import os
+import tempfile
+from codegen import Plain
+
+p = Plain("a", 1)
+assert p.to_base64() == base64.b64encode(bytes(p)).decode()
+assert Plain.from_base64(p.to_base64()) == p
+try:
+    Plain.from_base64("not base64!")
+except ValueError as e:
+    assert "invalid base64 string" in str(e)
+else:
+    raise AssertionError("invalid base64 should be rejected")
+assert p.to_borsh() == b"\x01\x00\x00\x00a\x01\x00\x00\x00\x00\x00\x00\x00"
+assert Plain.from_borsh(p.to_borsh()) == p
+try:
+    Plain.from_borsh(b"\x01")
+except ValueError as e:
+    assert "failed to deserialize from borsh" in str(e)
+else:
+    raise AssertionError("truncated borsh should be rejected")
+assert Plain.from_yaml(p.to_yaml()) == p
+assert Plain.from_msgpack(p.to_msgpack()) == p
+schema = Plain.schema()
+assert set(schema["properties"]) == {"name", "value"}
+with tempfile.TemporaryDirectory() as d:
+    path = os.path.join(d, "plain.json")
+    p.write_json_file(path)
+    assert Plain.from_json_file(path) == p
+if hasattr(p, "to_dict"):
+    assert p.to_dict() == {"name": "a", "value": 1}
+    assert Plain.from_dict({"name": "a", "value": 1}) == p
+"#,
+    );
+}
+
+#[test]
+fn pickle_variants() {
+    run_python(
+        "pickle_variants",
+        r#"
+import pickle
+from codegen import Buffered, NewArgs, Point, Stateful
+
+n = NewArgs.from_value(3)
+assert pickle.loads(pickle.dumps(n)).to_json() == n.to_json()
+assert pickle.loads(pickle.dumps(n, protocol=2)).to_json() == n.to_json()
+
+b = Buffered(b"abc")
+buffers = []
+data = pickle.dumps(b, protocol=5, buffer_callback=buffers.append)
+assert len(buffers) == 1
+assert pickle.loads(data, buffers=buffers).to_json() == b.to_json()
+assert pickle.loads(pickle.dumps(b)).to_json() == b.to_json()
+
+assert Stateful(7).__getnewargs__() == (7,)
+s = pickle.loads(pickle.dumps(Stateful(7)))
+assert type(s) is Stateful and s.value == 7
+
+p = pickle.loads(pickle.dumps(Point(1, -2)))
+assert (p.x, p.y) == (1, -2)
+"#,
+    );
+}
+
+#[test]
+fn versioned_and_string_methods() {
+    run_python(
+        "versioned_and_string_methods",
+        r#"
+from codegen import Text, Versioned
+
+v = Versioned(5)
+assert v.to_bytes_versioned() == b"\x01\x05"
+assert Versioned.from_bytes_versioned(b"\x01\x05").to_bytes_versioned() == b"\x01\x05"
+assert Versioned.from_bytes_legacy(b"\x05").to_bytes_versioned() == b"\x01\x05"
+assert v.version == 1
+
+t = Text.from_string("hi")
+assert str(t) == "hi"
+assert repr(t) == 'Text("hi")', repr(t)
+try:
+    Text.from_string("")
+except ValueError as e:
+    assert "empty text" in str(e)
+else:
+    raise AssertionError("empty text should be rejected")
+"#,
+    );
+}
+
+#[test]
+fn rpc_methods() {
+    run_python(
+        "rpc_methods",
+        r#"
+import pickle
+from codegen import BalanceResp, GetBalance
+
+g = GetBalance(4)
+assert g.id == 4
+assert GetBalance.from_json(g.to_json()) == g
+assert pickle.loads(pickle.dumps(g)) == g
+
+r = BalanceResp(10)
+assert BalanceResp.from_json(r.to_json()) == r
+assert BalanceResp.from_bytes(bytes(r)) == r
+assert r.clone() == r and r.clone() is not r
+"#,
+    );
+}
+
+#[test]
+fn pickle_state_subclasses() {
+    run_python(
+        "pickle_state_subclasses",
+        r#"
+import copy
+import pickle
+from codegen import FrozenState, Stateful
+
+class Sub(Stateful):
+    pass
+
+class FrozenSub(FrozenState):
+    pass
+
+for protocol in range(pickle.HIGHEST_PROTOCOL + 1):
+    s = pickle.loads(pickle.dumps(Sub(7), protocol=protocol))
+    assert type(s) is Sub and s.value == 7
+    f = pickle.loads(pickle.dumps(FrozenSub(bytes(FrozenState.from_value(3))), protocol=protocol))
+    assert type(f) is FrozenSub and f.value == 3
+assert type(copy.copy(Sub(1))) is Sub
+f = pickle.loads(pickle.dumps(FrozenState.from_value(5)))
+assert type(f) is FrozenState and f.value == 5
+"#,
+    );
+}
@@ -0,0 +1,32 @@
+//! `write_stubs` joins the fragments `pyi_stub` records into one file per module.
+#![cfg(feature = "stubs")]
+
+#[test]
+fn stubs() {
+    let dest = std::env::temp_dir().join(format!("solders-macros-stubs-{}", std::process::id()));
+    solders_macros_codegen_tests::write_stubs(&dest).unwrap();
+    let pairs = std::fs::read_to_string(dest.join("pairs.pyi")).unwrap();
+    let keys = std::fs::read_to_string(dest.join("keys.pyi")).unwrap();
+    std::fs::remove_dir_all(&dest).unwrap();
+
+    assert!(
+        pairs.contains("from solders.keys import Key\n"),
+        "{}",
+        pairs
+    );
+    assert_eq!(pairs.matches("class KeyPair:").count(), 1, "{}", pairs);
+    assert!(pairs.contains("    def __init__(self, left: Key, right: Key) -> None: ...\n"));
+    assert!(
+        pairs.contains("    @property\n    def left(self) -> Key:\n"),
+        "{}",
+        pairs
+    );
+    #[cfg(feature = "multiple-pymethods")]
+    assert!(
+        pairs.contains("    def swapped(self) -> KeyPair: ...\n"),
+        "{}",
+        pairs
+    );
+    assert!(keys.contains("class Key:\n"), "{}", keys);
+    assert!(!keys.contains("from solders."), "{}", keys);
+}
@@ -0,0 +1,16 @@
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/wrong_item.rs");
+    t.compile_fail("tests/ui/missing_argument.rs");
+    t.compile_fail("tests/ui/unrecognized_argument.rs");
+    t.compile_fail("tests/ui/invalid_argument.rs");
+    t.compile_fail("tests/ui/generic.rs");
+    t.compile_fail("tests/ui/py_exceptions_clash.rs");
+    t.compile_fail("tests/ui/conflict.rs");
+    // With `multiple-pymethods` the remaining methods go in a `#[pyo3::pymethods]` block,
+    // which doesn't resolve here since the tests don't depend on pyo3.
+    if !cfg!(feature = "multiple-pymethods") {
+        t.compile_fail("tests/ui/duplicate_method.rs");
+    }
+}
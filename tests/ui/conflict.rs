@@ -0,0 +1,12 @@
+struct Foo(u8);
+
+#[solders_macros::common_methods]
+#[solders_macros::getnewargs]
+impl Foo {
+    #[new]
+    fn new(value: u8) -> Self {
+        Self(value)
+    }
+}
+
+fn main() {}
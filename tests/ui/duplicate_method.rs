@@ -0,0 +1,11 @@
+#[derive(Debug)]
+struct Foo;
+
+#[solders_macros::debug_repr]
+impl Foo {
+    fn __repr__(&self) -> String {
+        String::new()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+struct Foo<T>(T);
+
+#[solders_macros::register_class(submodule = "foo")]
+impl<T> Foo<T> {}
+
+fn main() {}
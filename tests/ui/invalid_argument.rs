@@ -0,0 +1,6 @@
+#[solders_macros::enum_str_mapping(rename_all = "Sentence case")]
+enum Foo {
+    A,
+}
+
+fn main() {}
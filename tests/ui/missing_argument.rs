@@ -0,0 +1,6 @@
+struct Foo(Vec<u8>);
+
+#[solders_macros::pyiterator(item = u8)]
+impl Foo {}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[solders_macros::py_exceptions]
+#[derive(Debug)]
+pub enum FooError {
+    FooError,
+}
+
+fn main() {}
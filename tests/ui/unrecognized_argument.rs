@@ -0,0 +1,7 @@
+#[derive(Debug)]
+struct Foo;
+
+#[solders_macros::debug_repr(colour = "red")]
+impl Foo {}
+
+fn main() {}
@@ -0,0 +1,4 @@
+#[solders_macros::pyhash]
+struct Foo;
+
+fn main() {}
@@ -0,0 +1,15 @@
+//! Runs separately from `ui.rs`, since it points `OUT_DIR` at a file for the whole process.
+//!
+//! Skipped with `multiple-pymethods`, for the same reason as `duplicate_method.rs` in `ui.rs`.
+#![cfg(all(feature = "metadata", not(feature = "multiple-pymethods")))]
+
+#[test]
+fn ui_io() {
+    // Metadata goes in `$OUT_DIR/solders_metadata`, which can't be created under a file.
+    std::env::set_var(
+        "OUT_DIR",
+        concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"),
+    );
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/io.rs");
+}